@@ -1,137 +1,268 @@
+use crate::symbol::Symbol;
+use serde::Serialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+/// A byte-offset range into a source file, produced by the parser from the
+/// underlying `pest::Span` and threaded through the AST so runtime values
+/// and errors can be traced back to where they came from.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub file: Option<String>,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, file: Option<String>) -> Self {
+        Self { start, end, file }
+    }
+
+    /// A span with no known location, used by nodes synthesized after
+    /// parsing (e.g. by a desugaring pass) with no single source origin.
+    pub fn synthetic() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct BinaryExpr {
     pub op: String,
     pub left: Box<ASTNode>,
     pub right: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UnaryExpr {
     pub op: String,
     pub expr: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CallExpr {
     pub name: Box<ASTNode>,
     pub args: Vec<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct VarExpr {
-    pub name: String,
+    pub name: Symbol,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+/// The literal form a `Num` was written in, chosen at lex time by whether
+/// the token contained a `.` or an exponent. `NumExpr.v` always holds the
+/// evaluated value; `kind` preserves which form the source used, so a
+/// pass like `diagnostics::is_integer_typed` can tell `arr[5]` from `arr[2.7]`
+/// without re-parsing the source text.
+#[derive(Debug, Clone, Serialize)]
+pub enum NumKind {
+    Int(i64),
+    Float(f64),
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct NumExpr {
     pub v: f64,
+    pub kind: NumKind,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StrExpr {
     pub v: String,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MeExpr {
-    pub field: String,
+    pub field: Symbol,
+    pub span: Span,
 }
 
 // Tuple: (expr1, expr2, ...)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TupleExpr {
     pub items: Vec<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct IndexExpr {
     pub base: Box<ASTNode>,
     pub index: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StrandAccessExpr {
     pub base: Box<ASTNode>,
     pub out: Box<ASTNode>,
+    /// Set for a one-frame-delay read (e.g. `@prev a.x`), marking this as
+    /// a feedback access rather than an ordinary same-frame dependency.
+    pub delayed: bool,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StrandRemapExpr {
     pub base: Box<ASTNode>,
-    pub strand: String,
+    pub strand: Symbol,
     pub mappings: Vec<AxisMapping>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AxisMapping {
     pub axis: Box<ASTNode>,
     pub expr: Box<ASTNode>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct IfExpr {
     pub condition: Box<ASTNode>,
     pub then_expr: Box<ASTNode>,
     pub else_expr: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// A single `pattern => body` arm inside a `MatchExpr`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: ASTNode,
 }
 
-#[derive(Debug, Clone)]
+/// What a `MatchArm` tests the scrutinee against. `Wildcard` (`_`) always
+/// matches; the parser (`build_match_expr`) rejects any `match` that
+/// doesn't end with one, so evaluation never needs a "no arm matched"
+/// fallback.
+#[derive(Debug, Clone, Serialize)]
+pub enum Pattern {
+    Num(f64),
+    Str(String),
+    Wildcard,
+}
+
+/// `match scrutinee { pattern => body, ... }`. Arms are tried in source
+/// order; the first whose pattern matches the scrutinee's evaluated value
+/// wins, the same way `IfExpr`'s condition picks `then_expr` or
+/// `else_expr`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchExpr {
+    pub scrutinee: Box<ASTNode>,
+    pub arms: Vec<MatchArm>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct AssignmentExpr {
-    pub name: String,
+    pub name: Symbol,
     pub op: String,
     pub expr: Box<ASTNode>,
     pub is_output: bool,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NamedArg {
     pub name: String,
     pub value: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BackendExpr {
     pub context: String,
     pub args: Vec<ASTNode>,
     pub named_args: HashMap<String, ASTNode>,
     pub positional_args: Vec<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SpindleDef {
-    pub name: String,
-    pub inputs: Vec<String>,
-    pub outputs: Vec<String>,
+    pub name: Symbol,
+    pub inputs: Vec<Symbol>,
+    pub outputs: Vec<Symbol>,
     pub body: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InstanceBindExpr {
     pub name: String,
     pub outputs: Vec<String>,
     pub expr: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// A `#kind arg1 arg2 ...` compiler directive. `args` is the whitespace-
+/// split pragma body verbatim -- unrecognized `kind`s are left for
+/// runtime to validate (see `parser::apply_pragma`'s strict-mode case for
+/// the one parse-time check that does exist).
+#[derive(Debug, Clone, Serialize)]
+pub struct PragmaExpr {
+    pub kind: String,
+    pub args: Vec<String>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Program {
     pub statements: Vec<ASTNode>,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BlockExpr {
     pub body: Vec<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+/// `return <expr?>` inside a spindle body. Ends the body early -- the
+/// `exhaustive_spindle_outputs` diagnostic treats a path that reaches a
+/// `Return` as satisfying every declared output, the same way reaching a
+/// `return` ends a typed function's need to fall through to its tail
+/// expression.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReturnExpr {
+    pub expr: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+/// What a `for` loop iterates over.
+#[derive(Debug, Clone, Serialize)]
+pub enum ForKind {
+    /// `for i in (start to end [step step])` -- counts from `start` to
+    /// `end`.
+    Range {
+        start: Box<ASTNode>,
+        end: Box<ASTNode>,
+        /// Defaults to `+1` (or `-1` when lowering detects a descending
+        /// range) when the source omits `step s`.
+        step: Option<Box<ASTNode>>,
+    },
+    /// `for x in iterable` -- iterates the elements of an indexable value
+    /// (an array, or a strand reached through `StrandAccess`/`StrandRemap`)
+    /// directly, rather than counting an index.
+    Each { iterable: Box<ASTNode> },
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ForLoopExpr {
     pub var: String,
-    pub start: Box<ASTNode>,
-    pub end: Box<ASTNode>,
+    pub kind: ForKind,
+    /// Runs once, in place of the loop, when `kind` is a `Range` whose
+    /// range is empty (`start >= end` ascending, or `start <= end`
+    /// descending) or an `Each` whose iterable is empty.
+    pub else_body: Option<Box<ASTNode>>,
     pub body: Box<ASTNode>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
 pub enum ASTNode {
     // Expressions
     Binary(BinaryExpr),
@@ -146,6 +277,7 @@ pub enum ASTNode {
     StrandAccess(StrandAccessExpr),
     StrandRemap(StrandRemapExpr),
     If(IfExpr),
+    Match(MatchExpr),
 
     // Statements
     Assignment(AssignmentExpr),
@@ -154,6 +286,41 @@ pub enum ASTNode {
     SpindleDef(SpindleDef),
     InstanceBinding(InstanceBindExpr),
     ForLoop(ForLoopExpr),
+    Return(ReturnExpr),
+    Pragma(PragmaExpr),
     Block(BlockExpr),
     Program(Program),
 }
+
+impl ASTNode {
+    /// The source span covering this node, or `None` for `Program` (which
+    /// is a container with no single location). Nodes synthesized without
+    /// source provenance return their `Span::synthetic()` span.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            ASTNode::Binary(n) => Some(&n.span),
+            ASTNode::Unary(n) => Some(&n.span),
+            ASTNode::Call(n) => Some(&n.span),
+            ASTNode::Var(n) => Some(&n.span),
+            ASTNode::Num(n) => Some(&n.span),
+            ASTNode::Str(n) => Some(&n.span),
+            ASTNode::Me(n) => Some(&n.span),
+            ASTNode::Tuple(n) => Some(&n.span),
+            ASTNode::Index(n) => Some(&n.span),
+            ASTNode::StrandAccess(n) => Some(&n.span),
+            ASTNode::StrandRemap(n) => Some(&n.span),
+            ASTNode::If(n) => Some(&n.span),
+            ASTNode::Match(n) => Some(&n.span),
+            ASTNode::Assignment(n) => Some(&n.span),
+            ASTNode::NamedArg(n) => Some(&n.span),
+            ASTNode::Backend(n) => Some(&n.span),
+            ASTNode::SpindleDef(n) => Some(&n.span),
+            ASTNode::InstanceBinding(n) => Some(&n.span),
+            ASTNode::ForLoop(n) => Some(&n.span),
+            ASTNode::Return(n) => Some(&n.span),
+            ASTNode::Pragma(n) => Some(&n.span),
+            ASTNode::Block(n) => Some(&n.span),
+            ASTNode::Program(_) => None,
+        }
+    }
+}
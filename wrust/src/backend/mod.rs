@@ -4,5 +4,10 @@ pub mod cpu_audio;
 pub mod cpu_compute;
 pub mod metal_visual;
 pub mod metal_audio;
+pub mod rtp;
 
-pub use types::{Backend, DataRef, HandleType};
\ No newline at end of file
+pub use types::{
+    AsyncBackend, Backend, CoordsBatch, DataRef, HandleKind, HandleType, SubmitToken, SyncAdapter,
+    Transport,
+};
+pub use rtp::RtpBackend;
\ No newline at end of file
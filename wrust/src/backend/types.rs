@@ -1,25 +1,142 @@
 use crate::runtime::backend_registry::Context;
+use crate::runtime::env::Superbeats;
 use crate::runtime::render_graph::Subgraph;
 use crate::runtime::Coordinator;
-use crate::Env;
 use crate::utils::Result;
+use crate::Env;
 use crate::WeftError;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
 
 pub type HandleType = u32;
 
+/// The kind of zero-copy handle a backend can produce or consume, for
+/// `Coordinator::negotiate_transports` to match a producer and a
+/// downstream consumer against each other before deciding whether a
+/// cross-context edge can share a handle or needs a CPU round-trip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    GpuTexture2D,
+    AudioRingBuffer,
+    CpuBuffer,
+}
+
+/// How one cross-context edge actually moves data at runtime, as decided
+/// by `Coordinator::negotiate_transports`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Producer and consumer agreed on `HandleKind`; the edge reads a
+    /// shared handle directly, no CPU round-trip.
+    Handle(HandleKind),
+    /// No shared `HandleKind`; the edge reads through `get_value_at` (or
+    /// `get_values_batch`) one value at a time instead.
+    ValueBridge,
+}
+
+/// A columnar coordinate set for `get_values_batch`: one contiguous slice
+/// of values per axis (`"x"` paired with every sample's x-coordinate,
+/// say) instead of `get_value_at`'s one-`HashMap`-per-point shape, so a
+/// whole scanline's coordinates can be evaluated without allocating a
+/// map per pixel.
+pub struct CoordsBatch<'a> {
+    len: usize,
+    axes: HashMap<String, &'a [f64]>,
+}
+
+impl<'a> CoordsBatch<'a> {
+    /// `len` is the number of points in the batch; every axis added via
+    /// `with_axis` must be exactly this long.
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            axes: HashMap::new(),
+        }
+    }
+
+    pub fn with_axis(mut self, name: impl Into<String>, values: &'a [f64]) -> Self {
+        debug_assert_eq!(values.len(), self.len, "CoordsBatch axis length mismatch");
+        self.axes.insert(name.into(), values);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Builds the single-point `HashMap` `get_value_at` expects for
+    /// sample `index`, for `get_values_batch`'s scalar-loop default.
+    pub fn point(&self, index: usize) -> HashMap<String, f64> {
+        self.axes
+            .iter()
+            .map(|(name, values)| (name.clone(), values[index]))
+            .collect()
+    }
+}
+
 pub enum DataRef<'a> {
     ValueGetter(Box<dyn Fn(&HashMap<String, f64>, &Env, &Coordinator) -> Result<f64> + 'a>),
+    BatchGetter(
+        Box<dyn Fn(&CoordsBatch, &Env, &Coordinator, &mut [f64]) -> Result<()> + 'a>,
+    ),
     Handle(HandleType),
 }
 
-pub trait Backend {
+/// `Send` so a `Coordinator` can hold backends behind a `Mutex` and drive
+/// `execute_subgraph` for independent contexts from separate worker
+/// threads (see `Coordinator::execute`'s dependency-level scheduling).
+pub trait Backend: Send {
     fn context(&self) -> Context;
 
     fn supports_handles(&self) -> bool {
         false
     }
 
+    /// Handle kinds this backend can hand out from `get_handle` -- e.g. a
+    /// GPU backend producing `HandleKind::GpuTexture2D` for a render
+    /// target. `Coordinator::negotiate_transports` matches this against a
+    /// downstream consumer's `accepted_handle_kinds` before wiring a
+    /// cross-context edge as a shared handle rather than a CPU round-trip.
+    /// Defaults to `[HandleKind::CpuBuffer]` when `supports_handles` is
+    /// true (matching the bool's old meaning: *some* handle, of no
+    /// particular kind), or nothing otherwise.
+    fn produced_handle_kinds(&self) -> Vec<HandleKind> {
+        if self.supports_handles() {
+            vec![HandleKind::CpuBuffer]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Handle kinds this backend can consume directly -- e.g. an audio
+    /// backend that can read an `AudioRingBuffer` handle without going
+    /// through `get_value_at`. Same default as `produced_handle_kinds`:
+    /// `[HandleKind::CpuBuffer]` if `supports_handles` is true, nothing
+    /// otherwise.
+    fn accepted_handle_kinds(&self) -> Vec<HandleKind> {
+        if self.supports_handles() {
+            vec![HandleKind::CpuBuffer]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Whether this backend may run `compile_subgraph`/`execute_subgraph`
+    /// concurrently with other backends from a `Coordinator`-managed
+    /// worker thread (`true` by default). A backend with thread-affine
+    /// state -- a graphics context tied to the thread that created it, say
+    /// -- should return `false` to opt out; `Coordinator::execute` then
+    /// runs that backend's whole dependency level sequentially on the
+    /// calling thread instead of spawning it.
+    fn supports_parallel(&self) -> bool {
+        true
+    }
+
     fn compile_subgraph(
         &mut self,
         subgraph: &Subgraph,
@@ -34,6 +151,27 @@ pub trait Backend {
         coordinator: &Coordinator,
     ) -> Result<()>;
 
+    /// Non-blocking counterpart to `execute_subgraph`, driven by
+    /// `Coordinator::execute_async`. A backend whose execution is really
+    /// just device-buffer I/O -- submitting samples to an audio callback,
+    /// flushing a frame to a display -- can override this to hand back a
+    /// future that only resolves once that I/O completes, instead of
+    /// blocking the thread that's also driving an unrelated backend's
+    /// compute in the same dependency level. Defaults to running
+    /// `execute_subgraph` synchronously up front and handing back an
+    /// already-ready future, so a backend that has no such I/O to overlap
+    /// doesn't need to implement this at all.
+    fn execute_subgraph_async<'a>(
+        &'a mut self,
+        subgraph: &'a Subgraph,
+        env: &'a Env,
+        coordinator: &'a Coordinator,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + 'a>> {
+        Box::pin(std::future::ready(
+            self.execute_subgraph(subgraph, env, coordinator),
+        ))
+    }
+
     fn get_value_at(
         &self,
         instance: &str,
@@ -43,6 +181,29 @@ pub trait Backend {
         coordinator: &Coordinator,
     ) -> Result<f64>;
 
+    /// Batched counterpart to `get_value_at`: evaluates `instance@output`
+    /// at every point in `coords_batch` in one call, writing each result
+    /// into the matching slot of `out` (`out.len() == coords_batch.len()`),
+    /// instead of a `HashMap` allocation and a boxed-closure call per
+    /// point. Defaults to looping `get_value_at` once per point, so an
+    /// existing backend keeps working unchanged; a CPU backend evaluating
+    /// a whole scanline can override this to SIMD or parallelize across
+    /// the batch instead.
+    fn get_values_batch(
+        &self,
+        instance: &str,
+        output: &str,
+        coords_batch: &CoordsBatch,
+        env: &Env,
+        coordinator: &Coordinator,
+        out: &mut [f64],
+    ) -> Result<()> {
+        for i in 0..coords_batch.len() {
+            out[i] = self.get_value_at(instance, output, &coords_batch.point(i), env, coordinator)?;
+        }
+        Ok(())
+    }
+
     fn get_handle(&self, instance: &str, output: &str) -> Result<HandleType> {
         Err(WeftError::Runtime(format!(
             "Backend {} does not support handles for {}@{}",
@@ -51,4 +212,245 @@ pub trait Backend {
             output
         )))
     }
-}
\ No newline at end of file
+
+    /// Applies a value change to `instance@output` scheduled for `at_beat`,
+    /// dispatched by `Coordinator::dispatch_scheduled_events` once that
+    /// beat falls inside the run-ahead window. Backends that can't act on
+    /// scheduled changes (most can just apply `value` immediately, the
+    /// same as a live edit) don't need to override this.
+    fn schedule_value_change(
+        &mut self,
+        instance: &str,
+        output: &str,
+        at_beat: Superbeats,
+        value: f64,
+    ) -> Result<()> {
+        let _ = (at_beat, value);
+        Err(WeftError::Runtime(format!(
+            "Backend {} does not support scheduled value changes for {}@{}",
+            self.context().name(),
+            instance,
+            output
+        )))
+    }
+}
+
+/// Forwards every method to the boxed backend, so a `Box<dyn Backend>`
+/// (what callers already have on hand) can itself be wrapped in
+/// `SyncAdapter` -- `SyncAdapter<Box<dyn Backend>>` below -- without
+/// needing its own concrete backend type.
+impl Backend for Box<dyn Backend> {
+    fn context(&self) -> Context {
+        (**self).context()
+    }
+
+    fn supports_handles(&self) -> bool {
+        (**self).supports_handles()
+    }
+
+    fn produced_handle_kinds(&self) -> Vec<HandleKind> {
+        (**self).produced_handle_kinds()
+    }
+
+    fn accepted_handle_kinds(&self) -> Vec<HandleKind> {
+        (**self).accepted_handle_kinds()
+    }
+
+    fn supports_parallel(&self) -> bool {
+        (**self).supports_parallel()
+    }
+
+    fn compile_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<()> {
+        (**self).compile_subgraph(subgraph, env, coordinator)
+    }
+
+    fn execute_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<()> {
+        (**self).execute_subgraph(subgraph, env, coordinator)
+    }
+
+    fn execute_subgraph_async<'a>(
+        &'a mut self,
+        subgraph: &'a Subgraph,
+        env: &'a Env,
+        coordinator: &'a Coordinator,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + 'a>> {
+        (**self).execute_subgraph_async(subgraph, env, coordinator)
+    }
+
+    fn get_value_at(
+        &self,
+        instance: &str,
+        output: &str,
+        coords: &HashMap<String, f64>,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<f64> {
+        (**self).get_value_at(instance, output, coords, env, coordinator)
+    }
+
+    fn get_values_batch(
+        &self,
+        instance: &str,
+        output: &str,
+        coords_batch: &CoordsBatch,
+        env: &Env,
+        coordinator: &Coordinator,
+        out: &mut [f64],
+    ) -> Result<()> {
+        (**self).get_values_batch(instance, output, coords_batch, env, coordinator, out)
+    }
+
+    fn get_handle(&self, instance: &str, output: &str) -> Result<HandleType> {
+        (**self).get_handle(instance, output)
+    }
+
+    fn schedule_value_change(
+        &mut self,
+        instance: &str,
+        output: &str,
+        at_beat: Superbeats,
+        value: f64,
+    ) -> Result<()> {
+        (**self).schedule_value_change(instance, output, at_beat, value)
+    }
+}
+
+/// Identifies one in-flight `AsyncBackend::submit_subgraph` call, to hand
+/// back to `poll_submit`. Only meaningful to the backend that issued it --
+/// a backend is free to hand out whatever values suit it (an index into
+/// its own pending-submission list, say), the same way `HandleType` is
+/// opaque outside the backend that minted it.
+pub type SubmitToken = u64;
+
+/// A submit/poll dispatch surface, mirroring the split between a
+/// blocking and non-blocking client (send-and-confirm vs.
+/// fire-and-forget): `submit_subgraph` enqueues a frame's worth of work
+/// -- a GPU command buffer, an audio callback registration -- and
+/// returns immediately with a token, rather than blocking until that
+/// work lands the way `Backend::execute_subgraph` does. `poll_submit`
+/// then checks whether a previously issued token's work has finished.
+///
+/// This is a separate axis of concurrency from `Backend::
+/// execute_subgraph_async`'s future-based overlap within one
+/// `Coordinator::execute_async` call: submit/poll lets the coordinator
+/// kick off *every* backend's work for a frame up front, then drain
+/// completions, so e.g. the visual backend's next frame can start
+/// submitting while audio is still draining the current one.
+pub trait AsyncBackend: Backend {
+    fn submit_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<SubmitToken>;
+
+    fn poll_submit(&self, token: SubmitToken) -> Poll<Result<()>>;
+}
+
+/// Adapts any synchronous `Backend` into an `AsyncBackend` that gets no
+/// real overlap: `submit_subgraph` just runs `execute_subgraph` to
+/// completion on the spot, and `poll_submit` always reports it already
+/// done. This is the "default adapter" a backend that only implements
+/// the sync trait is wrapped in so `Coordinator`'s submit/poll scheduling
+/// mode keeps working for it, same as any other backend.
+pub struct SyncAdapter<B: Backend>(pub B);
+
+impl<B: Backend> Backend for SyncAdapter<B> {
+    fn context(&self) -> Context {
+        self.0.context()
+    }
+
+    fn supports_handles(&self) -> bool {
+        self.0.supports_handles()
+    }
+
+    fn produced_handle_kinds(&self) -> Vec<HandleKind> {
+        self.0.produced_handle_kinds()
+    }
+
+    fn accepted_handle_kinds(&self) -> Vec<HandleKind> {
+        self.0.accepted_handle_kinds()
+    }
+
+    fn supports_parallel(&self) -> bool {
+        self.0.supports_parallel()
+    }
+
+    fn compile_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<()> {
+        self.0.compile_subgraph(subgraph, env, coordinator)
+    }
+
+    fn execute_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<()> {
+        self.0.execute_subgraph(subgraph, env, coordinator)
+    }
+
+    fn execute_subgraph_async<'a>(
+        &'a mut self,
+        subgraph: &'a Subgraph,
+        env: &'a Env,
+        coordinator: &'a Coordinator,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + 'a>> {
+        self.0.execute_subgraph_async(subgraph, env, coordinator)
+    }
+
+    fn get_value_at(
+        &self,
+        instance: &str,
+        output: &str,
+        coords: &HashMap<String, f64>,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<f64> {
+        self.0.get_value_at(instance, output, coords, env, coordinator)
+    }
+
+    fn get_handle(&self, instance: &str, output: &str) -> Result<HandleType> {
+        self.0.get_handle(instance, output)
+    }
+
+    fn schedule_value_change(
+        &mut self,
+        instance: &str,
+        output: &str,
+        at_beat: Superbeats,
+        value: f64,
+    ) -> Result<()> {
+        self.0.schedule_value_change(instance, output, at_beat, value)
+    }
+}
+
+impl<B: Backend> AsyncBackend for SyncAdapter<B> {
+    fn submit_subgraph(
+        &mut self,
+        subgraph: &Subgraph,
+        env: &Env,
+        coordinator: &Coordinator,
+    ) -> Result<SubmitToken> {
+        self.0.execute_subgraph(subgraph, env, coordinator)?;
+        Ok(0)
+    }
+
+    fn poll_submit(&self, _token: SubmitToken) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
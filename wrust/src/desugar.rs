@@ -0,0 +1,135 @@
+//! Post-macro-expansion AST rewrites that change how a program is
+//! expressed without changing its behavior.
+
+use crate::ast::*;
+
+/// NOTE: does *not* implement the compound-assignment-hoisting feature
+/// its request asked for (`arr[f()] += 5` / `image@rgb(...) += x`
+/// double-evaluating their place's sub-expressions). That request's
+/// premise doesn't hold in this tree: `AssignmentExpr.name` is a plain
+/// `Symbol`, and `parser::build_assignment`/`build_output_assignment`
+/// only ever parse a bare identifier as an assignment's target -- there
+/// is no `arr[i] += 1` / `image@rgb(...) += v` lvalue syntax anywhere in
+/// the grammar (hypothetical or otherwise) for a hoisting pass to act on.
+/// Unlike the other grammar gaps this crate routinely papers over with a
+/// "the grammar would need..." doc comment (`return_stmt`, `pipe_expr`,
+/// `range_clause`), a complex assignment lvalue isn't a rule this pass
+/// can bolt on by itself: it requires `AssignmentExpr` to grow a real
+/// lvalue target in place of `name: Symbol`, which ripples into every
+/// site that reads an assignment's target (`render_graph`, `coordinator`,
+/// `diagnostics`, `macros`, both printers) -- a change bigger than this
+/// pass and out of scope for it to make unilaterally.
+///
+/// Flagging this back rather than shipping a pass that can't run: this
+/// function is left as a structural no-op (see `test_complex_lvalue_assignment_does_not_parse`
+/// below, which pins down the actual gap) until `AssignmentExpr` grows
+/// that lvalue target.
+pub fn desugar_program(program: &Program) -> Program {
+    Program {
+        statements: program.statements.iter().map(desugar_statement).collect(),
+    }
+}
+
+fn desugar_statement(stmt: &ASTNode) -> ASTNode {
+    match stmt {
+        ASTNode::SpindleDef(def) => ASTNode::SpindleDef(SpindleDef {
+            name: def.name,
+            inputs: def.inputs.clone(),
+            outputs: def.outputs.clone(),
+            body: Box::new(desugar_statement(&def.body)),
+            span: def.span.clone(),
+        }),
+        ASTNode::Block(block) => ASTNode::Block(BlockExpr {
+            body: block.body.iter().map(desugar_block_statement).collect(),
+            span: block.span.clone(),
+        }),
+        ASTNode::ForLoop(f) => ASTNode::ForLoop(ForLoopExpr {
+            var: f.var.clone(),
+            kind: match &f.kind {
+                ForKind::Range { start, end, step } => ForKind::Range {
+                    start: start.clone(),
+                    end: end.clone(),
+                    step: step.clone(),
+                },
+                ForKind::Each { iterable } => ForKind::Each {
+                    iterable: iterable.clone(),
+                },
+            },
+            else_body: f.else_body.as_ref().map(|e| Box::new(desugar_statement(e))),
+            body: Box::new(desugar_statement(&f.body)),
+            span: f.span.clone(),
+        }),
+        other => other.clone(),
+    }
+}
+
+/// Desugars one statement inside a `Block.body`. A compound `Assignment`
+/// against a complex lvalue would hoist here (see `desugar_program`'s doc);
+/// every `Assignment` parses with a bare `Var` target today, so this
+/// always passes the statement through unchanged.
+fn desugar_block_statement(stmt: &ASTNode) -> ASTNode {
+    match stmt {
+        ASTNode::ForLoop(_) => desugar_statement(stmt),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_assignment_to_a_simple_var_passes_through_unchanged() {
+        let program = Program {
+            statements: vec![ASTNode::SpindleDef(SpindleDef {
+                name: crate::symbol::intern("test"),
+                inputs: vec![],
+                outputs: vec![crate::symbol::intern("x")],
+                body: Box::new(ASTNode::Block(BlockExpr {
+                    body: vec![ASTNode::Assignment(AssignmentExpr {
+                        name: crate::symbol::intern("x"),
+                        op: "+=".to_string(),
+                        expr: Box::new(ASTNode::Num(NumExpr {
+                            v: 5.0,
+                            kind: NumKind::Int(5),
+                            span: Span::synthetic(),
+                        })),
+                        is_output: false,
+                        span: Span::synthetic(),
+                    })],
+                    span: Span::synthetic(),
+                })),
+                span: Span::synthetic(),
+            })],
+        };
+
+        let desugared = desugar_program(&program);
+
+        match &desugared.statements[0] {
+            ASTNode::SpindleDef(def) => match def.body.as_ref() {
+                ASTNode::Block(block) => match &block.body[0] {
+                    ASTNode::Assignment(assign) => {
+                        assert_eq!(assign.name, "x");
+                        assert_eq!(assign.op, "+=");
+                    }
+                    other => panic!("expected Assignment, got {:?}", other),
+                },
+                other => panic!("expected Block, got {:?}", other),
+            },
+            other => panic!("expected SpindleDef, got {:?}", other),
+        }
+    }
+
+    /// Pins down the actual gap cited in `desugar_program`'s doc: a
+    /// compound assignment against a complex lvalue isn't a "nothing to
+    /// hoist" case this pass declines to touch, it's source text the
+    /// parser rejects outright. If this test ever starts failing (the
+    /// source parses), this pass needs `AssignmentExpr` to grow a real lvalue target before it
+    /// can actually do its job.
+    #[test]
+    fn test_complex_lvalue_assignment_does_not_parse() {
+        let result = crate::parser::parse("spindle test() :: <x> { arr[f()] += 5 }");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,765 @@
+//! Lint-style checks for `weft check`: unlike `WeftError`, which bails out
+//! of parsing/graph-building on the first problem, a `Diagnostic` just
+//! describes one thing wrong with an already-parsed `Program` so `cmd_check`
+//! can collect every problem in a single pass (and `--fix` can apply every
+//! fixable one together).
+
+use crate::ast::{ASTNode, AssignmentExpr, ForKind, NumKind, Program, Span};
+use crate::utils::error::render_span;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Severity::Error => "✗",
+            Severity::Warning => "⚠",
+            Severity::Info => "ℹ",
+            Severity::Hint => "·",
+        }
+    }
+}
+
+/// An autofix for a `Diagnostic`: replace the source bytes covered by
+/// `span` with `replacement`.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    pub fix: Option<TextEdit>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            span: None,
+            fix: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_fix(mut self, fix: TextEdit) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// A caret-underlined rendering of this diagnostic against `source`,
+    /// matching the style `WeftError::render` uses for parse/runtime errors.
+    pub fn render(&self, source: &str) -> String {
+        let header = format!(
+            "{} {}: {}",
+            self.severity.icon(),
+            self.severity.label(),
+            self.message
+        );
+        match &self.span {
+            Some(span) => render_span(source, span, &header),
+            None => header,
+        }
+    }
+}
+
+/// Accumulates diagnostics across a compile pass (parsing, resolution, ...)
+/// instead of bailing out at the first problem, so the frontend can report
+/// every issue found in a single run. Only converts into the fatal
+/// `WeftError` once at least one `Severity::Error` entry has been pushed;
+/// warnings and lower don't block the next stage.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.entries.push(diagnostic);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether any collected diagnostic is severe enough to halt the
+    /// compile pass.
+    pub fn has_errors(&self) -> bool {
+        has_error(&self.entries)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.entries.iter()
+    }
+
+    /// Converts the collected diagnostics into a fatal `WeftError` if any
+    /// rose to `Severity::Error`, joining their messages into one report.
+    /// Returns `Ok(())` otherwise, leaving the warnings/notes for the
+    /// caller to render however it likes.
+    pub fn into_result(self) -> crate::utils::Result<()> {
+        if !self.has_errors() {
+            return Ok(());
+        }
+        let details = self
+            .entries
+            .into_iter()
+            .filter(|d| d.severity == Severity::Error)
+            .map(|d| d.message)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(crate::WeftError::Runtime(details))
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Runs every check against `program`, returning all diagnostics found,
+/// ordered by where they occur in the source.
+pub fn check(program: &Program) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    diagnostics.extend(unused_spindles(program));
+    diagnostics.extend(undeclared_outputs(program));
+    diagnostics.extend(unknown_strand_outputs(program));
+    diagnostics.extend(shadowed_environment_assignments(program));
+    diagnostics.extend(exhaustive_spindle_outputs(program));
+    diagnostics.extend(integer_typed_places(program));
+    diagnostics.sort_by_key(|d| d.span.as_ref().map(|s| s.start).unwrap_or(usize::MAX));
+    diagnostics
+}
+
+/// Whether `diagnostics` contains anything severe enough that `weft check`
+/// should exit non-zero.
+pub fn has_error(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// Applies every fixable diagnostic's edit to `source`. Edits are sorted
+/// back-to-front by start offset first so applying an earlier edit can't
+/// invalidate the byte range of one that comes after it in the file.
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut edits: Vec<&TextEdit> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    edits.sort_by(|a, b| b.span.start.cmp(&a.span.start));
+
+    let mut fixed = source.to_string();
+    for edit in edits {
+        fixed.replace_range(edit.span.start..edit.span.end, &edit.replacement);
+    }
+    fixed
+}
+
+/// Visits `node` and every node reachable from it, depth-first. Unlike
+/// `render_graph`'s `find_deps_in_expr` (which only descends through
+/// dependency-relevant expression nodes), this covers the whole AST,
+/// including spindle bodies and blocks, since a shadowed-output access or
+/// dead spindle can be nested anywhere.
+fn walk<'a>(node: &'a ASTNode, visit: &mut impl FnMut(&'a ASTNode)) {
+    visit(node);
+    match node {
+        ASTNode::Binary(n) => {
+            walk(&n.left, visit);
+            walk(&n.right, visit);
+        }
+        ASTNode::Unary(n) => walk(&n.expr, visit),
+        ASTNode::Call(n) => {
+            walk(&n.name, visit);
+            for arg in &n.args {
+                walk(arg, visit);
+            }
+        }
+        ASTNode::Var(_) | ASTNode::Num(_) | ASTNode::Str(_) | ASTNode::Me(_) | ASTNode::Pragma(_) => {}
+        ASTNode::Tuple(n) => {
+            for item in &n.items {
+                walk(item, visit);
+            }
+        }
+        ASTNode::Index(n) => {
+            walk(&n.base, visit);
+            walk(&n.index, visit);
+        }
+        ASTNode::StrandAccess(n) => {
+            walk(&n.base, visit);
+            walk(&n.out, visit);
+        }
+        ASTNode::StrandRemap(n) => {
+            walk(&n.base, visit);
+            for mapping in &n.mappings {
+                walk(&mapping.axis, visit);
+                walk(&mapping.expr, visit);
+            }
+        }
+        ASTNode::If(n) => {
+            walk(&n.condition, visit);
+            walk(&n.then_expr, visit);
+            walk(&n.else_expr, visit);
+        }
+        ASTNode::Match(n) => {
+            walk(&n.scrutinee, visit);
+            for arm in &n.arms {
+                walk(&arm.body, visit);
+            }
+        }
+        ASTNode::Assignment(n) => walk(&n.expr, visit),
+        ASTNode::NamedArg(n) => walk(&n.value, visit),
+        ASTNode::Backend(n) => {
+            for arg in &n.positional_args {
+                walk(arg, visit);
+            }
+            for arg in n.named_args.values() {
+                walk(arg, visit);
+            }
+        }
+        ASTNode::SpindleDef(n) => walk(&n.body, visit),
+        ASTNode::InstanceBinding(n) => walk(&n.expr, visit),
+        ASTNode::ForLoop(n) => {
+            match &n.kind {
+                ForKind::Range { start, end, step } => {
+                    walk(start, visit);
+                    walk(end, visit);
+                    if let Some(step) = step {
+                        walk(step, visit);
+                    }
+                }
+                ForKind::Each { iterable } => walk(iterable, visit),
+            }
+            walk(&n.body, visit);
+            if let Some(else_body) = &n.else_body {
+                walk(else_body, visit);
+            }
+        }
+        ASTNode::Block(n) => {
+            for stmt in &n.body {
+                walk(stmt, visit);
+            }
+        }
+        ASTNode::Return(n) => {
+            if let Some(expr) = &n.expr {
+                walk(expr, visit);
+            }
+        }
+        ASTNode::Program(p) => {
+            for stmt in &p.statements {
+                walk(stmt, visit);
+            }
+        }
+    }
+}
+
+/// Warns about `spindle` definitions that no `Call` in the program ever
+/// instantiates. Fixable by deleting the dead definition.
+///
+/// Walks the AST directly for `Call`s rather than going through
+/// `Env::spindles`, which nothing in the crate ever populates from a
+/// parsed `Program`.
+fn unused_spindles(program: &Program) -> Vec<Diagnostic> {
+    let mut called = HashSet::new();
+    for stmt in &program.statements {
+        walk(stmt, &mut |node| {
+            if let ASTNode::Call(call) = node {
+                if let ASTNode::Var(var) = call.name.as_ref() {
+                    called.insert(var.name.resolve());
+                }
+            }
+        });
+    }
+
+    program
+        .statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            ASTNode::SpindleDef(def) if !called.contains(def.name.resolve()) => Some(def),
+            _ => None,
+        })
+        .map(|def| {
+            Diagnostic::warning(format!(
+                "spindle `{}` is defined but never instantiated",
+                def.name
+            ))
+            .with_span(def.span.clone())
+            .with_fix(TextEdit {
+                span: def.span.clone(),
+                replacement: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Errors on instance bindings whose `<...>` output list declares more
+/// outputs than the binding's expression actually produces (a `Tuple`
+/// shorter than the declared outputs). Not fixable: there's no way to
+/// guess what the missing values should be.
+fn undeclared_outputs(program: &Program) -> Vec<Diagnostic> {
+    program
+        .statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            ASTNode::InstanceBinding(bind) => Some(bind),
+            _ => None,
+        })
+        .flat_map(|bind| {
+            let produced = match bind.expr.as_ref() {
+                ASTNode::Tuple(tuple) => tuple.items.len(),
+                _ => bind.outputs.len(),
+            };
+            let produced = produced.min(bind.outputs.len());
+            bind.outputs[produced..].iter().map(move |output| {
+                Diagnostic::error(format!(
+                    "instance `{}` declares output `{}`, but its binding only produces {} value(s)",
+                    bind.name, output, produced
+                ))
+                .with_span(bind.span.clone())
+            })
+        })
+        .collect()
+}
+
+/// Errors on `StrandAccess`es to an output name the referenced instance
+/// never declared in its `<...>` output list. Not fixable: there's no way
+/// to guess the intended output.
+fn unknown_strand_outputs(program: &Program) -> Vec<Diagnostic> {
+    let mut declared_outputs: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for stmt in &program.statements {
+        if let ASTNode::InstanceBinding(bind) = stmt {
+            declared_outputs
+                .entry(bind.name.as_str())
+                .or_default()
+                .extend(bind.outputs.iter().map(String::as_str));
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for stmt in &program.statements {
+        walk(stmt, &mut |node| {
+            let ASTNode::StrandAccess(access) = node else {
+                return;
+            };
+            let ASTNode::Var(base) = access.base.as_ref() else {
+                return;
+            };
+            let ASTNode::Var(out) = access.out.as_ref() else {
+                return;
+            };
+            let Some(outputs) = declared_outputs.get(base.name.resolve()) else {
+                return;
+            };
+            if !outputs.contains(out.name.resolve()) {
+                diagnostics.push(
+                    Diagnostic::error(format!(
+                        "instance `{}` does not export output `{}`",
+                        base.name, out.name
+                    ))
+                    .with_span(access.span.clone()),
+                );
+            }
+        });
+    }
+    diagnostics
+}
+
+/// Warns about a top-level `me<name> = ...` environment assignment that a
+/// later one in the same program overwrites before it can ever be read.
+/// Fixable by deleting the shadowed (earlier) assignment.
+fn shadowed_environment_assignments(program: &Program) -> Vec<Diagnostic> {
+    let env_assignments: Vec<&AssignmentExpr> = program
+        .statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            ASTNode::Assignment(assign) if !assign.is_output => Some(assign),
+            _ => None,
+        })
+        .collect();
+
+    let mut last_index: HashMap<&str, usize> = HashMap::new();
+    for (i, assign) in env_assignments.iter().enumerate() {
+        last_index.insert(assign.name.resolve(), i);
+    }
+
+    env_assignments
+        .iter()
+        .enumerate()
+        .filter(|(i, assign)| last_index.get(assign.name.resolve()) != Some(i))
+        .map(|(_, assign)| {
+            Diagnostic::warning(format!(
+                "this assignment to `{}` is shadowed by a later one and has no effect",
+                assign.name
+            ))
+            .with_span(assign.span.clone())
+            .with_fix(TextEdit {
+                span: assign.span.clone(),
+                replacement: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// The result of walking one control-flow path through a spindle body:
+/// either it's guaranteed to hit a `return` before falling off the end (in
+/// which case the exhaustiveness requirement is satisfied regardless of
+/// what was assigned), or it falls through having assigned at least the
+/// given outputs.
+enum PathOutcome<'a> {
+    Returns,
+    FallsThrough(HashSet<&'a str>),
+}
+
+fn block_statements(node: &ASTNode) -> &[ASTNode] {
+    match node {
+        ASTNode::Block(b) => &b.body,
+        _ => &[],
+    }
+}
+
+/// Combines the outcomes of two branches that are mutually exclusive
+/// alternatives (a for-loop's body vs. its `else`): if both return, so
+/// does the combination; if only one does, the other's guarantees carry
+/// through; if neither does, only outputs both branches assign are
+/// guaranteed (a conditional assignment on just one side isn't exhaustive).
+fn combine_branches<'a>(a: PathOutcome<'a>, b: PathOutcome<'a>) -> PathOutcome<'a> {
+    match (a, b) {
+        (PathOutcome::Returns, PathOutcome::Returns) => PathOutcome::Returns,
+        (PathOutcome::Returns, PathOutcome::FallsThrough(set))
+        | (PathOutcome::FallsThrough(set), PathOutcome::Returns) => PathOutcome::FallsThrough(set),
+        (PathOutcome::FallsThrough(a), PathOutcome::FallsThrough(b)) => {
+            PathOutcome::FallsThrough(a.intersection(&b).copied().collect())
+        }
+    }
+}
+
+/// Analyzes one branch of an `If`/`Match` as its own path: a `Block`
+/// branch (e.g. a for-loop body) walks its statements like `analyze_path`
+/// always has, while anything else -- including every `IfExpr.then_expr`/
+/// `else_expr` and `MatchArm.body` today, which `build_expr` always
+/// builds as a value expression, never a `build_block` statement list --
+/// is analyzed as a single-statement path of just that node.
+fn analyze_branch<'a>(node: &'a ASTNode) -> PathOutcome<'a> {
+    match node {
+        ASTNode::Block(b) => analyze_path(&b.body),
+        other => analyze_path(std::slice::from_ref(other)),
+    }
+}
+
+/// Walks `stmts` as a straight-line path, forking at each `ForLoop` into
+/// its body and (if present) its `else`, and at each `If`/`Match` into its
+/// branches/arms. A `for` loop with no `else` may run zero iterations, so
+/// nothing its body assigns or returns is guaranteed; one with an `else`
+/// guarantees whichever the two branches agree on. An `If` always has both
+/// branches, and a `Match` always has at least one arm, so both combine
+/// their branches/arms the same way.
+fn analyze_path<'a>(stmts: &'a [ASTNode]) -> PathOutcome<'a> {
+    let mut assigned = HashSet::new();
+    for stmt in stmts {
+        match stmt {
+            ASTNode::Assignment(a) if a.is_output => {
+                assigned.insert(a.name.resolve());
+            }
+            ASTNode::Return(_) => return PathOutcome::Returns,
+            ASTNode::ForLoop(for_loop) => {
+                let body_outcome = analyze_path(block_statements(&for_loop.body));
+                let loop_outcome = match &for_loop.else_body {
+                    Some(else_body) => {
+                        combine_branches(body_outcome, analyze_path(block_statements(else_body)))
+                    }
+                    None => PathOutcome::FallsThrough(HashSet::new()),
+                };
+                match loop_outcome {
+                    PathOutcome::Returns => return PathOutcome::Returns,
+                    PathOutcome::FallsThrough(set) => assigned.extend(set),
+                }
+            }
+            ASTNode::If(if_expr) => {
+                let outcome = combine_branches(
+                    analyze_branch(&if_expr.then_expr),
+                    analyze_branch(&if_expr.else_expr),
+                );
+                match outcome {
+                    PathOutcome::Returns => return PathOutcome::Returns,
+                    PathOutcome::FallsThrough(set) => assigned.extend(set),
+                }
+            }
+            ASTNode::Match(match_expr) => {
+                let mut outcome = None;
+                for arm in &match_expr.arms {
+                    let arm_outcome = analyze_branch(&arm.body);
+                    outcome = Some(match outcome {
+                        None => arm_outcome,
+                        Some(acc) => combine_branches(acc, arm_outcome),
+                    });
+                }
+                match outcome {
+                    Some(PathOutcome::Returns) => return PathOutcome::Returns,
+                    Some(PathOutcome::FallsThrough(set)) => assigned.extend(set),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    PathOutcome::FallsThrough(assigned)
+}
+
+/// Errors on a `spindle` whose declared `:: <outputs>` aren't all assigned
+/// (via an `is_output` assignment, or bypassed with a `return`) on every
+/// control path through its body. Not fixable: there's no way to guess
+/// which branch is missing the assignment.
+fn exhaustive_spindle_outputs(program: &Program) -> Vec<Diagnostic> {
+    program
+        .statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            ASTNode::SpindleDef(def) => Some(def),
+            _ => None,
+        })
+        .flat_map(|def| {
+            let assigned = match analyze_path(block_statements(&def.body)) {
+                PathOutcome::Returns => return Vec::new(),
+                PathOutcome::FallsThrough(assigned) => assigned,
+            };
+            def.outputs
+                .iter()
+                .filter(|output| !assigned.contains(output.resolve()))
+                .map(|output| {
+                    Diagnostic::error(format!(
+                        "spindle `{}` does not assign output `{}` on every control path",
+                        def.name, output
+                    ))
+                    .with_span(def.span.clone())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Infers whether `expr` evaluates to an integer, without evaluating it.
+/// Returns `None` when that can't be determined from the expression alone
+/// (e.g. a `Var` naming a spindle input, whose type isn't known here) --
+/// `None` means "don't know", not "not an integer", so callers only act
+/// on a definite `Some(false)`. Arithmetic stays integer-typed as long as
+/// every operand does; mixing in a single float-typed operand makes the
+/// whole expression float-typed, matching ordinary numeric promotion.
+fn is_integer_typed(expr: &ASTNode) -> Option<bool> {
+    match expr {
+        ASTNode::Num(n) => Some(matches!(n.kind, NumKind::Int(_))),
+        ASTNode::Unary(u) if u.op == "-" => is_integer_typed(&u.expr),
+        ASTNode::Binary(b) if matches!(b.op.as_str(), "+" | "-" | "*") => {
+            match (is_integer_typed(&b.left), is_integer_typed(&b.right)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Errors on an `Index.index` or a `ForLoop::Range` bound/step that
+/// `is_integer_typed` can tell is float-valued -- `arr[2.7]` and a
+/// fractional loop counter both silently truncate today. Not fixable:
+/// truncating vs. rounding vs. rejecting the value is a choice for the
+/// author, not this pass.
+fn integer_typed_places(program: &Program) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for stmt in &program.statements {
+        walk(stmt, &mut |node| match node {
+            ASTNode::Index(idx) => {
+                if is_integer_typed(&idx.index) == Some(false) {
+                    diagnostics.push(
+                        Diagnostic::error("index expression must be integer-typed")
+                            .with_span(idx.span.clone()),
+                    );
+                }
+            }
+            ASTNode::ForLoop(for_loop) => {
+                if let ForKind::Range { start, end, step } = &for_loop.kind {
+                    let bounds = [Some(start.as_ref()), Some(end.as_ref()), step.as_deref()];
+                    if bounds
+                        .into_iter()
+                        .flatten()
+                        .any(|bound| is_integer_typed(bound) == Some(false))
+                    {
+                        diagnostics.push(
+                            Diagnostic::error("for-loop range bounds must be integer-typed")
+                                .with_span(for_loop.span.clone()),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        });
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{IfExpr, MatchArm, MatchExpr, NumExpr, Pattern, SpindleDef};
+
+    fn num(v: f64) -> ASTNode {
+        ASTNode::Num(NumExpr {
+            v,
+            kind: NumKind::Int(v as i64),
+            span: Span::synthetic(),
+        })
+    }
+
+    fn output_assignment(name: &str) -> ASTNode {
+        ASTNode::Assignment(AssignmentExpr {
+            name: crate::symbol::intern(name),
+            op: "=".to_string(),
+            expr: Box::new(ASTNode::Num(NumExpr {
+                v: 1.0,
+                kind: NumKind::Int(1),
+                span: Span::synthetic(),
+            })),
+            is_output: true,
+            span: Span::synthetic(),
+        })
+    }
+
+    fn spindle(outputs: &[&str], body: ASTNode) -> Program {
+        Program {
+            statements: vec![ASTNode::SpindleDef(SpindleDef {
+                name: crate::symbol::intern("test"),
+                inputs: vec![],
+                outputs: outputs.iter().map(|o| crate::symbol::intern(o)).collect(),
+                body: Box::new(ASTNode::Block(crate::ast::BlockExpr {
+                    body: vec![body],
+                    span: Span::synthetic(),
+                })),
+                span: Span::synthetic(),
+            })],
+        }
+    }
+
+    #[test]
+    fn if_else_that_exhaustively_assigns_an_output_has_no_error() {
+        let program = spindle(
+            &["x"],
+            ASTNode::If(IfExpr {
+                condition: Box::new(num(1.0)),
+                then_expr: Box::new(output_assignment("x")),
+                else_expr: Box::new(output_assignment("x")),
+                span: Span::synthetic(),
+            }),
+        );
+
+        assert!(exhaustive_spindle_outputs(&program).is_empty());
+    }
+
+    #[test]
+    fn if_else_that_only_assigns_on_one_branch_is_an_error() {
+        let program = spindle(
+            &["x"],
+            ASTNode::If(IfExpr {
+                condition: Box::new(num(1.0)),
+                then_expr: Box::new(output_assignment("x")),
+                else_expr: Box::new(output_assignment("other")),
+                span: Span::synthetic(),
+            }),
+        );
+
+        assert_eq!(exhaustive_spindle_outputs(&program).len(), 1);
+    }
+
+    #[test]
+    fn match_whose_arms_all_assign_an_output_has_no_error() {
+        let program = spindle(
+            &["x"],
+            ASTNode::Match(MatchExpr {
+                scrutinee: Box::new(num(1.0)),
+                arms: vec![
+                    MatchArm {
+                        pattern: Pattern::Num(1.0),
+                        body: output_assignment("x"),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Wildcard,
+                        body: output_assignment("x"),
+                    },
+                ],
+                span: Span::synthetic(),
+            }),
+        );
+
+        assert!(exhaustive_spindle_outputs(&program).is_empty());
+    }
+
+    #[test]
+    fn match_whose_arms_dont_all_assign_an_output_is_an_error() {
+        let program = spindle(
+            &["x"],
+            ASTNode::Match(MatchExpr {
+                scrutinee: Box::new(num(1.0)),
+                arms: vec![
+                    MatchArm {
+                        pattern: Pattern::Num(1.0),
+                        body: output_assignment("x"),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Wildcard,
+                        body: output_assignment("other"),
+                    },
+                ],
+                span: Span::synthetic(),
+            }),
+        );
+
+        assert_eq!(exhaustive_spindle_outputs(&program).len(), 1);
+    }
+}
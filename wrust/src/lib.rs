@@ -1,5 +1,10 @@
 pub mod ast;
+pub mod desugar;
+pub mod diagnostics;
 pub mod parser;
+pub mod print;
+pub mod macros;
+pub mod symbol;
 pub mod value;
 pub mod utils;
 pub mod runtime;
@@ -7,7 +12,11 @@ pub mod backend;
 pub mod compilers;
 
 pub use ast::*;
+pub use desugar::desugar_program;
 pub use parser::*;
+pub use print::format_program;
+pub use macros::{expand_program, MacroDef, MacroTable};
+pub use symbol::Symbol;
 pub use value::*;
-pub use utils::error::{WeftError, Result};
+pub use utils::error::{ResultExt, WeftError, Result};
 pub use runtime::Env;
@@ -0,0 +1,683 @@
+//! Compile-time macro/template expansion, run over the `ast` after
+//! `parser` but before `runtime`/`compilers` see the tree — a syntax
+//! extension phase, not a runtime feature.
+//!
+//! This is expansion infrastructure only, not yet exposed to source
+//! syntax: `weft.pest` has no grammar rule yet for a `macro name(args)
+//! { ... }` definition, so `parser::parse` cannot produce [`MacroDef`]s
+//! directly, and there is no way for a `.weft` program to define a macro
+//! today. Until that grammar rule (and a `build_macro_def` analogous to
+//! `build_spindle_def`) exist, callers construct `MacroDef`s by hand and
+//! pass them to [`expand_program`].
+//!
+//! A [`MacroDef`] is a named template (`args` plus a `body` AST) that a
+//! `Call` node referencing that name is expanded into: call-site
+//! arguments are spliced in for the macro's parameters, and any other
+//! name the body binds itself (a `for` loop variable, an instance
+//! binding) is freshly renamed so it can't capture or collide with
+//! names at the call site. Expansion iterates to a fixpoint — a macro
+//! invoking another macro keeps expanding — under a recursion-depth
+//! guard.
+
+use crate::ast::*;
+use crate::symbol;
+use crate::utils::Result;
+use crate::WeftError;
+use std::collections::HashMap;
+
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+/// A user-defined macro template, built by hand today (see the module
+/// doc comment) rather than parsed from a `macro name(args) { ... }`
+/// source form.
+#[derive(Debug, Clone)]
+pub struct MacroDef {
+    pub name: String,
+    pub args: Vec<String>,
+    pub body: ASTNode,
+}
+
+/// Macros available to [`expand_program`], keyed by name. Never populated
+/// by `parser::parse` -- see the module doc comment.
+pub type MacroTable = HashMap<String, MacroDef>;
+
+/// Expands every macro call in `program` against `macros`, returning a
+/// new, fully-expanded `Program`.
+pub fn expand_program(program: &Program, macros: &MacroTable) -> Result<Program> {
+    let statements = program
+        .statements
+        .iter()
+        .map(|stmt| expand_node(stmt, macros, 0))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Program { statements })
+}
+
+fn expand_node(node: &ASTNode, macros: &MacroTable, depth: usize) -> Result<ASTNode> {
+    if depth > MAX_EXPANSION_DEPTH {
+        return Err(WeftError::Runtime(format!(
+            "macro expansion exceeded depth limit of {} (possible recursive macro)",
+            MAX_EXPANSION_DEPTH
+        )));
+    }
+
+    if let ASTNode::Call(call) = node {
+        if let ASTNode::Var(v) = call.name.as_ref() {
+            if let Some(def) = macros.get(v.name.resolve()) {
+                let expanded_args = call
+                    .args
+                    .iter()
+                    .map(|a| expand_node(a, macros, depth + 1))
+                    .collect::<Result<Vec<_>>>()?;
+                let instantiated = instantiate_macro(def, &expanded_args, &call.span);
+                return expand_node(&instantiated, macros, depth + 1);
+            }
+        }
+    }
+
+    Ok(match node {
+        ASTNode::Binary(b) => ASTNode::Binary(BinaryExpr {
+            op: b.op.clone(),
+            left: Box::new(expand_node(&b.left, macros, depth)?),
+            right: Box::new(expand_node(&b.right, macros, depth)?),
+            span: b.span.clone(),
+        }),
+        ASTNode::Unary(u) => ASTNode::Unary(UnaryExpr {
+            op: u.op.clone(),
+            expr: Box::new(expand_node(&u.expr, macros, depth)?),
+            span: u.span.clone(),
+        }),
+        ASTNode::Call(c) => ASTNode::Call(CallExpr {
+            name: Box::new(expand_node(&c.name, macros, depth)?),
+            args: c
+                .args
+                .iter()
+                .map(|a| expand_node(a, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+            span: c.span.clone(),
+        }),
+        ASTNode::Tuple(t) => ASTNode::Tuple(TupleExpr {
+            items: t
+                .items
+                .iter()
+                .map(|i| expand_node(i, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+            span: t.span.clone(),
+        }),
+        ASTNode::Index(i) => ASTNode::Index(IndexExpr {
+            base: Box::new(expand_node(&i.base, macros, depth)?),
+            index: Box::new(expand_node(&i.index, macros, depth)?),
+            span: i.span.clone(),
+        }),
+        ASTNode::StrandAccess(s) => ASTNode::StrandAccess(StrandAccessExpr {
+            base: Box::new(expand_node(&s.base, macros, depth)?),
+            out: Box::new(expand_node(&s.out, macros, depth)?),
+            delayed: s.delayed,
+            span: s.span.clone(),
+        }),
+        ASTNode::StrandRemap(s) => ASTNode::StrandRemap(StrandRemapExpr {
+            base: Box::new(expand_node(&s.base, macros, depth)?),
+            strand: s.strand.clone(),
+            mappings: s
+                .mappings
+                .iter()
+                .map(|m| {
+                    Ok(AxisMapping {
+                        axis: Box::new(expand_node(&m.axis, macros, depth)?),
+                        expr: Box::new(expand_node(&m.expr, macros, depth)?),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            span: s.span.clone(),
+        }),
+        ASTNode::If(i) => ASTNode::If(IfExpr {
+            condition: Box::new(expand_node(&i.condition, macros, depth)?),
+            then_expr: Box::new(expand_node(&i.then_expr, macros, depth)?),
+            else_expr: Box::new(expand_node(&i.else_expr, macros, depth)?),
+            span: i.span.clone(),
+        }),
+        ASTNode::Match(m) => ASTNode::Match(MatchExpr {
+            scrutinee: Box::new(expand_node(&m.scrutinee, macros, depth)?),
+            arms: m
+                .arms
+                .iter()
+                .map(|arm| {
+                    Ok(MatchArm {
+                        pattern: arm.pattern.clone(),
+                        body: expand_node(&arm.body, macros, depth)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            span: m.span.clone(),
+        }),
+        ASTNode::Assignment(a) => ASTNode::Assignment(AssignmentExpr {
+            name: a.name.clone(),
+            op: a.op.clone(),
+            expr: Box::new(expand_node(&a.expr, macros, depth)?),
+            is_output: a.is_output,
+            span: a.span.clone(),
+        }),
+        ASTNode::NamedArg(n) => ASTNode::NamedArg(NamedArg {
+            name: n.name.clone(),
+            value: Box::new(expand_node(&n.value, macros, depth)?),
+            span: n.span.clone(),
+        }),
+        ASTNode::Backend(b) => ASTNode::Backend(BackendExpr {
+            context: b.context.clone(),
+            args: b
+                .args
+                .iter()
+                .map(|a| expand_node(a, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+            named_args: b
+                .named_args
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), expand_node(v, macros, depth)?)))
+                .collect::<Result<HashMap<_, _>>>()?,
+            positional_args: b
+                .positional_args
+                .iter()
+                .map(|a| expand_node(a, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+            span: b.span.clone(),
+        }),
+        ASTNode::SpindleDef(s) => ASTNode::SpindleDef(SpindleDef {
+            name: s.name.clone(),
+            inputs: s.inputs.clone(),
+            outputs: s.outputs.clone(),
+            body: Box::new(expand_node(&s.body, macros, depth)?),
+            span: s.span.clone(),
+        }),
+        ASTNode::InstanceBinding(b) => ASTNode::InstanceBinding(InstanceBindExpr {
+            name: b.name.clone(),
+            outputs: b.outputs.clone(),
+            expr: Box::new(expand_node(&b.expr, macros, depth)?),
+            span: b.span.clone(),
+        }),
+        ASTNode::ForLoop(f) => ASTNode::ForLoop(ForLoopExpr {
+            var: f.var.clone(),
+            kind: match &f.kind {
+                ForKind::Range { start, end, step } => ForKind::Range {
+                    start: Box::new(expand_node(start, macros, depth)?),
+                    end: Box::new(expand_node(end, macros, depth)?),
+                    step: step
+                        .as_ref()
+                        .map(|s| expand_node(s, macros, depth))
+                        .transpose()?
+                        .map(Box::new),
+                },
+                ForKind::Each { iterable } => ForKind::Each {
+                    iterable: Box::new(expand_node(iterable, macros, depth)?),
+                },
+            },
+            else_body: f
+                .else_body
+                .as_ref()
+                .map(|e| expand_node(e, macros, depth))
+                .transpose()?
+                .map(Box::new),
+            body: Box::new(expand_node(&f.body, macros, depth)?),
+            span: f.span.clone(),
+        }),
+        ASTNode::Block(b) => ASTNode::Block(BlockExpr {
+            body: b
+                .body
+                .iter()
+                .map(|s| expand_node(s, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+            span: b.span.clone(),
+        }),
+        ASTNode::Return(r) => ASTNode::Return(ReturnExpr {
+            expr: r
+                .expr
+                .as_ref()
+                .map(|e| expand_node(e, macros, depth))
+                .transpose()?
+                .map(Box::new),
+            span: r.span.clone(),
+        }),
+        ASTNode::Program(p) => ASTNode::Program(Program {
+            statements: p
+                .statements
+                .iter()
+                .map(|s| expand_node(s, macros, depth))
+                .collect::<Result<Vec<_>>>()?,
+        }),
+        ASTNode::Var(_) | ASTNode::Num(_) | ASTNode::Str(_) | ASTNode::Me(_) | ASTNode::Pragma(_) => {
+            node.clone()
+        }
+    })
+}
+
+/// Instantiates `def`'s body for one call site: its formal parameters
+/// are substituted with `call_args` (by position), every other name the
+/// body binds is freshly renamed for hygiene, and every node introduced
+/// by the template is re-stamped with `call_span` so diagnostics in the
+/// expanded code still point at the macro invocation rather than the
+/// macro's own definition site. Spliced-in argument subtrees keep their
+/// own spans, since they really did come from the call site.
+fn instantiate_macro(def: &MacroDef, call_args: &[ASTNode], call_span: &Span) -> ASTNode {
+    let subst: HashMap<String, ASTNode> = def
+        .args
+        .iter()
+        .cloned()
+        .zip(call_args.iter().cloned())
+        .collect();
+
+    let mut gensym = 0usize;
+    let mut renames: HashMap<String, String> = HashMap::new();
+    collect_locals(&def.body, &subst, &mut gensym, &mut renames);
+
+    substitute(&def.body, &subst, &renames, call_span)
+}
+
+/// Walks a macro body collecting every name it binds itself (not one of
+/// the macro's own parameters) and assigns each a fresh, unique name.
+fn collect_locals(
+    node: &ASTNode,
+    subst: &HashMap<String, ASTNode>,
+    gensym: &mut usize,
+    renames: &mut HashMap<String, String>,
+) {
+    let mut bind = |name: &str, gensym: &mut usize, renames: &mut HashMap<String, String>| {
+        if !subst.contains_key(name) && !renames.contains_key(name) {
+            *gensym += 1;
+            renames.insert(name.to_string(), format!("{}__macro{}", name, gensym));
+        }
+    };
+
+    match node {
+        ASTNode::ForLoop(f) => {
+            bind(&f.var, gensym, renames);
+            match &f.kind {
+                ForKind::Range { start, end, step } => {
+                    collect_locals(start, subst, gensym, renames);
+                    collect_locals(end, subst, gensym, renames);
+                    if let Some(step) = step {
+                        collect_locals(step, subst, gensym, renames);
+                    }
+                }
+                ForKind::Each { iterable } => collect_locals(iterable, subst, gensym, renames),
+            }
+            collect_locals(&f.body, subst, gensym, renames);
+            if let Some(else_body) = &f.else_body {
+                collect_locals(else_body, subst, gensym, renames);
+            }
+        }
+        ASTNode::InstanceBinding(b) => {
+            bind(&b.name, gensym, renames);
+            collect_locals(&b.expr, subst, gensym, renames);
+        }
+        ASTNode::Assignment(a) => {
+            bind(a.name.resolve(), gensym, renames);
+            collect_locals(&a.expr, subst, gensym, renames);
+        }
+        ASTNode::Block(b) => {
+            for stmt in &b.body {
+                collect_locals(stmt, subst, gensym, renames);
+            }
+        }
+        ASTNode::Binary(b) => {
+            collect_locals(&b.left, subst, gensym, renames);
+            collect_locals(&b.right, subst, gensym, renames);
+        }
+        ASTNode::Unary(u) => collect_locals(&u.expr, subst, gensym, renames),
+        ASTNode::Call(c) => {
+            collect_locals(&c.name, subst, gensym, renames);
+            for a in &c.args {
+                collect_locals(a, subst, gensym, renames);
+            }
+        }
+        ASTNode::Tuple(t) => {
+            for i in &t.items {
+                collect_locals(i, subst, gensym, renames);
+            }
+        }
+        ASTNode::Index(i) => {
+            collect_locals(&i.base, subst, gensym, renames);
+            collect_locals(&i.index, subst, gensym, renames);
+        }
+        ASTNode::StrandAccess(s) => {
+            collect_locals(&s.base, subst, gensym, renames);
+            collect_locals(&s.out, subst, gensym, renames);
+        }
+        ASTNode::StrandRemap(s) => {
+            collect_locals(&s.base, subst, gensym, renames);
+            for m in &s.mappings {
+                collect_locals(&m.axis, subst, gensym, renames);
+                collect_locals(&m.expr, subst, gensym, renames);
+            }
+        }
+        ASTNode::If(i) => {
+            collect_locals(&i.condition, subst, gensym, renames);
+            collect_locals(&i.then_expr, subst, gensym, renames);
+            collect_locals(&i.else_expr, subst, gensym, renames);
+        }
+        ASTNode::Match(m) => {
+            collect_locals(&m.scrutinee, subst, gensym, renames);
+            for arm in &m.arms {
+                collect_locals(&arm.body, subst, gensym, renames);
+            }
+        }
+        ASTNode::Backend(b) => {
+            for a in &b.args {
+                collect_locals(a, subst, gensym, renames);
+            }
+        }
+        ASTNode::NamedArg(n) => collect_locals(&n.value, subst, gensym, renames),
+        ASTNode::SpindleDef(s) => collect_locals(&s.body, subst, gensym, renames),
+        ASTNode::Return(r) => {
+            if let Some(expr) = &r.expr {
+                collect_locals(expr, subst, gensym, renames);
+            }
+        }
+        ASTNode::Var(_)
+        | ASTNode::Num(_)
+        | ASTNode::Str(_)
+        | ASTNode::Me(_)
+        | ASTNode::Pragma(_)
+        | ASTNode::Program(_) => {}
+    }
+}
+
+/// Rebuilds `node` with parameters substituted, local binders renamed,
+/// and every template-introduced node re-stamped with `call_span`.
+fn substitute(
+    node: &ASTNode,
+    subst: &HashMap<String, ASTNode>,
+    renames: &HashMap<String, String>,
+    call_span: &Span,
+) -> ASTNode {
+    let sub = |n: &ASTNode| substitute(n, subst, renames, call_span);
+
+    match node {
+        ASTNode::Var(v) => {
+            if let Some(replacement) = subst.get(v.name.resolve()) {
+                replacement.clone()
+            } else {
+                let name = renames
+                    .get(v.name.resolve())
+                    .map(|renamed| symbol::intern(renamed))
+                    .unwrap_or(v.name);
+                ASTNode::Var(VarExpr { name, span: call_span.clone() })
+            }
+        }
+        ASTNode::Num(n) => ASTNode::Num(NumExpr {
+            v: n.v,
+            kind: n.kind.clone(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Str(s) => ASTNode::Str(StrExpr { v: s.v.clone(), span: call_span.clone() }),
+        ASTNode::Me(m) => ASTNode::Me(MeExpr { field: m.field.clone(), span: call_span.clone() }),
+        ASTNode::Pragma(p) => ASTNode::Pragma(PragmaExpr {
+            kind: p.kind.clone(),
+            args: p.args.clone(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Binary(b) => ASTNode::Binary(BinaryExpr {
+            op: b.op.clone(),
+            left: Box::new(sub(&b.left)),
+            right: Box::new(sub(&b.right)),
+            span: call_span.clone(),
+        }),
+        ASTNode::Unary(u) => ASTNode::Unary(UnaryExpr {
+            op: u.op.clone(),
+            expr: Box::new(sub(&u.expr)),
+            span: call_span.clone(),
+        }),
+        ASTNode::Call(c) => ASTNode::Call(CallExpr {
+            name: Box::new(sub(&c.name)),
+            args: c.args.iter().map(sub).collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Tuple(t) => ASTNode::Tuple(TupleExpr {
+            items: t.items.iter().map(sub).collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Index(i) => ASTNode::Index(IndexExpr {
+            base: Box::new(sub(&i.base)),
+            index: Box::new(sub(&i.index)),
+            span: call_span.clone(),
+        }),
+        ASTNode::StrandAccess(s) => ASTNode::StrandAccess(StrandAccessExpr {
+            base: Box::new(sub(&s.base)),
+            out: Box::new(sub(&s.out)),
+            delayed: s.delayed,
+            span: call_span.clone(),
+        }),
+        ASTNode::StrandRemap(s) => ASTNode::StrandRemap(StrandRemapExpr {
+            base: Box::new(sub(&s.base)),
+            strand: s.strand.clone(),
+            mappings: s
+                .mappings
+                .iter()
+                .map(|m| AxisMapping { axis: Box::new(sub(&m.axis)), expr: Box::new(sub(&m.expr)) })
+                .collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::If(i) => ASTNode::If(IfExpr {
+            condition: Box::new(sub(&i.condition)),
+            then_expr: Box::new(sub(&i.then_expr)),
+            else_expr: Box::new(sub(&i.else_expr)),
+            span: call_span.clone(),
+        }),
+        ASTNode::Match(m) => ASTNode::Match(MatchExpr {
+            scrutinee: Box::new(sub(&m.scrutinee)),
+            arms: m
+                .arms
+                .iter()
+                .map(|arm| MatchArm {
+                    pattern: arm.pattern.clone(),
+                    body: sub(&arm.body),
+                })
+                .collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Assignment(a) => ASTNode::Assignment(AssignmentExpr {
+            name: renames
+                .get(a.name.resolve())
+                .map(|renamed| symbol::intern(renamed))
+                .unwrap_or(a.name),
+            op: a.op.clone(),
+            expr: Box::new(sub(&a.expr)),
+            is_output: a.is_output,
+            span: call_span.clone(),
+        }),
+        ASTNode::NamedArg(n) => ASTNode::NamedArg(NamedArg {
+            name: n.name.clone(),
+            value: Box::new(sub(&n.value)),
+            span: call_span.clone(),
+        }),
+        ASTNode::Backend(b) => ASTNode::Backend(BackendExpr {
+            context: b.context.clone(),
+            args: b.args.iter().map(sub).collect(),
+            named_args: b.named_args.iter().map(|(k, v)| (k.clone(), sub(v))).collect(),
+            positional_args: b.positional_args.iter().map(sub).collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::SpindleDef(s) => ASTNode::SpindleDef(SpindleDef {
+            name: s.name.clone(),
+            inputs: s.inputs.clone(),
+            outputs: s.outputs.clone(),
+            body: Box::new(sub(&s.body)),
+            span: call_span.clone(),
+        }),
+        ASTNode::InstanceBinding(b) => ASTNode::InstanceBinding(InstanceBindExpr {
+            name: renames.get(&b.name).cloned().unwrap_or_else(|| b.name.clone()),
+            outputs: b.outputs.clone(),
+            expr: Box::new(sub(&b.expr)),
+            span: call_span.clone(),
+        }),
+        ASTNode::ForLoop(f) => ASTNode::ForLoop(ForLoopExpr {
+            var: renames.get(&f.var).cloned().unwrap_or_else(|| f.var.clone()),
+            kind: match &f.kind {
+                ForKind::Range { start, end, step } => ForKind::Range {
+                    start: Box::new(sub(start)),
+                    end: Box::new(sub(end)),
+                    step: step.as_ref().map(|s| Box::new(sub(s))),
+                },
+                ForKind::Each { iterable } => ForKind::Each {
+                    iterable: Box::new(sub(iterable)),
+                },
+            },
+            else_body: f.else_body.as_ref().map(|e| Box::new(sub(e))),
+            body: Box::new(sub(&f.body)),
+            span: call_span.clone(),
+        }),
+        ASTNode::Block(b) => ASTNode::Block(BlockExpr {
+            body: b.body.iter().map(sub).collect(),
+            span: call_span.clone(),
+        }),
+        ASTNode::Return(r) => ASTNode::Return(ReturnExpr {
+            expr: r.expr.as_ref().map(|e| Box::new(sub(e))),
+            span: call_span.clone(),
+        }),
+        ASTNode::Program(p) => ASTNode::Program(Program {
+            statements: p.statements.iter().map(sub).collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> ASTNode {
+        ASTNode::Var(VarExpr { name: symbol::intern(name), span: Span::synthetic() })
+    }
+
+    fn num(v: f64) -> ASTNode {
+        let kind = if v.fract() == 0.0 {
+            NumKind::Int(v as i64)
+        } else {
+            NumKind::Float(v)
+        };
+        ASTNode::Num(NumExpr { v, kind, span: Span::synthetic() })
+    }
+
+    fn call_span() -> Span {
+        Span::new(10, 20, None)
+    }
+
+    #[test]
+    fn substitutes_parameters_with_call_args() {
+        let def = MacroDef {
+            name: "double".to_string(),
+            args: vec!["x".to_string()],
+            body: ASTNode::Binary(BinaryExpr {
+                op: "+".to_string(),
+                left: Box::new(var("x")),
+                right: Box::new(var("x")),
+                span: Span::synthetic(),
+            }),
+        };
+
+        let expanded = instantiate_macro(&def, &[num(5.0)], &call_span());
+
+        match expanded {
+            ASTNode::Binary(b) => {
+                assert!(matches!(b.left.as_ref(), ASTNode::Num(n) if n.v == 5.0));
+                assert!(matches!(b.right.as_ref(), ASTNode::Num(n) if n.v == 5.0));
+                assert_eq!(b.span, call_span());
+            }
+            _ => panic!("expected Binary"),
+        }
+    }
+
+    #[test]
+    fn renames_locals_the_body_introduces() {
+        let def = MacroDef {
+            name: "loopy".to_string(),
+            args: vec![],
+            body: ASTNode::ForLoop(ForLoopExpr {
+                var: "i".to_string(),
+                kind: ForKind::Range {
+                    start: Box::new(num(0.0)),
+                    end: Box::new(num(10.0)),
+                    step: None,
+                },
+                else_body: None,
+                body: Box::new(ASTNode::Block(BlockExpr { body: vec![], span: Span::synthetic() })),
+                span: Span::synthetic(),
+            }),
+        };
+
+        let expanded = instantiate_macro(&def, &[], &call_span());
+
+        match expanded {
+            ASTNode::ForLoop(f) => assert_ne!(f.var, "i"),
+            _ => panic!("expected ForLoop"),
+        }
+    }
+
+    #[test]
+    fn expand_program_expands_nested_macro_calls() {
+        let mut macros = MacroTable::new();
+        macros.insert(
+            "inc".to_string(),
+            MacroDef {
+                name: "inc".to_string(),
+                args: vec!["x".to_string()],
+                body: ASTNode::Binary(BinaryExpr {
+                    op: "+".to_string(),
+                    left: Box::new(var("x")),
+                    right: Box::new(num(1.0)),
+                    span: Span::synthetic(),
+                }),
+            },
+        );
+
+        let program = Program {
+            statements: vec![ASTNode::InstanceBinding(InstanceBindExpr {
+                name: "result".to_string(),
+                outputs: vec![],
+                expr: Box::new(ASTNode::Call(CallExpr {
+                    name: Box::new(var("inc")),
+                    args: vec![num(41.0)],
+                    span: call_span(),
+                })),
+                span: Span::synthetic(),
+            })],
+        };
+
+        let expanded = expand_program(&program, &macros).unwrap();
+
+        match &expanded.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Binary(b) => {
+                    assert_eq!(b.op, "+");
+                    assert!(matches!(b.left.as_ref(), ASTNode::Num(n) if n.v == 41.0));
+                }
+                other => panic!("expected Binary, got {:?}", other),
+            },
+            other => panic!("expected InstanceBinding, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recursive_macro_hits_depth_guard() {
+        let mut macros = MacroTable::new();
+        macros.insert(
+            "again".to_string(),
+            MacroDef {
+                name: "again".to_string(),
+                args: vec![],
+                body: ASTNode::Call(CallExpr {
+                    name: Box::new(var("again")),
+                    args: vec![],
+                    span: Span::synthetic(),
+                }),
+            },
+        );
+
+        let program = Program {
+            statements: vec![ASTNode::Call(CallExpr {
+                name: Box::new(var("again")),
+                args: vec![],
+                span: call_span(),
+            })],
+        };
+
+        assert!(expand_program(&program, &macros).is_err());
+    }
+}
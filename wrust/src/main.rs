@@ -1,7 +1,9 @@
 use clap::{Parser, Subcommand};
+use std::fmt::Write as _;
 use std::fs;
-use std::path::PathBuf;
-use wrust::{parser, Env, WeftError};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use wrust::{parser, Env, ResultExt, WeftError};
 
 #[derive(Parser)]
 #[command(name = "weft")]
@@ -18,6 +20,9 @@ enum Commands {
 
         #[arg(short, long)]
         pretty: bool,
+
+        #[arg(short = 'f', long, default_value = "text")]
+        format: OutputFormat,
     },
 
     Graph {
@@ -28,14 +33,31 @@ enum Commands {
 
         #[arg(short, long)]
         verbose: bool,
+
+        #[arg(short = 'f', long, default_value = "text")]
+        format: OutputFormat,
+
+        /// Append this run's graph-complexity metrics to `FILE` as JSON,
+        /// keyed by input file name and deep-merged with any object
+        /// already there, so a CI loop can accumulate one combined
+        /// metrics file across a suite of programs.
+        #[arg(long, value_name = "FILE")]
+        metrics: Option<PathBuf>,
     },
 
     Check {
         file: PathBuf,
+
+        /// Apply every fixable diagnostic's edit back to `file`.
+        #[arg(long)]
+        fix: bool,
     },
 
     Info {
         file: PathBuf,
+
+        #[arg(short = 'f', long, default_value = "text")]
+        format: OutputFormat,
     },
 
     Run {
@@ -49,27 +71,87 @@ enum Commands {
 
         #[arg(short, long, default_value = "60.0")]
         fps: f64,
+
+        /// Override an environment assignment from the command line, as
+        /// `KEY=VALUE` or `KEY=VALUE:TYPE` where `TYPE` is `int`, `float`,
+        /// `bool`, `bytes` (the default), or `timestamp(FMT)`. Repeatable.
+        #[arg(long = "set", value_name = "KEY=VALUE[:TYPE]")]
+        set: Vec<String>,
+    },
+
+    Fmt {
+        file: PathBuf,
+
+        /// Write the formatted output back to `file` instead of stdout.
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Run every `.weft` fixture in `dir` against its `//@ mode: ...`
+    /// annotation.
+    Test {
+        dir: PathBuf,
+
+        /// Regenerate `.expected` snapshots instead of comparing against them.
+        #[arg(long)]
+        bless: bool,
     },
 }
 
+/// Output format shared by `Parse`, `Graph`, and `Info`, for editors, LSP
+/// front-ends, and other tooling that wants structured data instead of the
+/// pretty-printer text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// Graphviz DOT. Only meaningful for `weft graph`.
+    Dot,
+}
+
+impl FromStr for OutputFormat {
+    type Err = WeftError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "dot" => Ok(OutputFormat::Dot),
+            other => Err(WeftError::Runtime(format!(
+                "unknown output format `{}` (expected `text`, `json`, or `dot`)",
+                other
+            ))),
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
     let result = match cli.command {
-        Commands::Parse { file, pretty } => cmd_parse(file, pretty),
+        Commands::Parse {
+            file,
+            pretty,
+            format,
+        } => cmd_parse(file, pretty, format),
         Commands::Graph {
             file,
             order,
             verbose,
-        } => cmd_graph(file, order, verbose),
-        Commands::Check { file } => cmd_check(file),
-        Commands::Info { file } => cmd_info(file),
+            format,
+            metrics,
+        } => cmd_graph(file, order, verbose, format, metrics),
+        Commands::Check { file, fix } => cmd_check(file, fix),
+        Commands::Info { file, format } => cmd_info(file, format),
         Commands::Run {
             file,
             width,
             height,
             fps,
-        } => cmd_run(file, width, height, fps),
+            set,
+        } => cmd_run(file, width, height, fps, set),
+        Commands::Fmt { file, write } => cmd_fmt(file, write),
+        Commands::Test { dir, bless } => cmd_test(dir, bless),
     };
 
     if let Err(e) = result {
@@ -83,208 +165,312 @@ fn read_file(path: PathBuf) -> Result<String, WeftError> {
         .map_err(|e| WeftError::Runtime(format!("Failed to read file {:?}: {}", path, e)))
 }
 
-fn cmd_parse(file: PathBuf, pretty: bool) -> Result<(), WeftError> {
-    let source = read_file(file)?;
-    let ast =
-        parser::parse(&source).map_err(|e| WeftError::Runtime(format!("Parse error: {}", e)))?;
+fn cmd_parse(file: PathBuf, pretty: bool, format: OutputFormat) -> Result<(), WeftError> {
+    let source = read_file(file.clone())?;
+    let ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
 
-    if pretty {
-        print_ast(&ast, 0);
-    } else {
-        println!("{:?}", ast);
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&ast)
+                .map_err(|e| WeftError::Runtime(format!("Failed to serialize AST: {}", e)))?;
+            println!("{}", json);
+        }
+        OutputFormat::Dot => {
+            return Err(WeftError::Runtime(
+                "`--format dot` is only supported by `weft graph`".to_string(),
+            ));
+        }
+        OutputFormat::Text if pretty => {
+            let mut out = String::new();
+            print_ast(&ast, 0, &mut out);
+            print!("{}", out);
+        }
+        OutputFormat::Text => println!("{:?}", ast),
     }
 
     Ok(())
 }
 
-fn print_ast(program: &wrust::Program, indent: usize) {
+/// Renders `program` as an indented tree into `out`, so both `cmd_parse`
+/// (which prints it) and `cmd_test`'s `Parse`-mode snapshots (which diff
+/// it) share one rendering.
+fn print_ast(program: &wrust::Program, indent: usize, out: &mut String) {
     let ind = "  ".repeat(indent);
-    println!("{}Program ({} statements)", ind, program.statements.len());
+    writeln!(
+        out,
+        "{}Program ({} statements)",
+        ind,
+        program.statements.len()
+    )
+    .unwrap();
     for stmt in &program.statements {
-        print_node(stmt, indent + 1);
+        print_node(stmt, indent + 1, out);
     }
 }
 
-fn print_node(node: &wrust::ASTNode, indent: usize) {
+fn print_node(node: &wrust::ASTNode, indent: usize, out: &mut String) {
     let ind = "  ".repeat(indent);
 
     match node {
         wrust::ASTNode::Backend(backend) => {
-            println!("{}Backend: {}", ind, backend.context);
+            writeln!(out, "{}Backend: {}", ind, backend.context).unwrap();
             for arg in &backend.positional_args {
-                print_node(arg, indent + 1);
+                print_node(arg, indent + 1, out);
             }
             for (name, value) in &backend.named_args {
-                println!("{}  {}: ", ind, name);
-                print_node(value, indent + 2);
+                writeln!(out, "{}  {}: ", ind, name).unwrap();
+                print_node(value, indent + 2, out);
             }
         }
         wrust::ASTNode::InstanceBinding(bind) => {
-            println!("{}Instance: {} <{}>", ind, bind.name, bind.outputs.join(", "));
-            print_node(&bind.expr, indent + 1);
+            writeln!(
+                out,
+                "{}Instance: {} <{}>",
+                ind,
+                bind.name,
+                bind.outputs.join(", ")
+            )
+            .unwrap();
+            print_node(&bind.expr, indent + 1, out);
         }
         wrust::ASTNode::SpindleDef(def) => {
-            println!(
+            let inputs: Vec<&str> = def.inputs.iter().map(|s| s.resolve()).collect();
+            let outputs: Vec<&str> = def.outputs.iter().map(|s| s.resolve()).collect();
+            writeln!(
+                out,
                 "{}Spindle: {}({}) :: <{}>",
                 ind,
                 def.name,
-                def.inputs.join(", "),
-                def.outputs.join(", ")
-            );
-            print_node(&def.body, indent + 1);
+                inputs.join(", "),
+                outputs.join(", ")
+            )
+            .unwrap();
+            print_node(&def.body, indent + 1, out);
         }
         wrust::ASTNode::Block(block) => {
-            println!("{}Block", ind);
+            writeln!(out, "{}Block", ind).unwrap();
             for stmt in &block.body {
-                print_node(stmt, indent + 1);
+                print_node(stmt, indent + 1, out);
             }
         }
         wrust::ASTNode::Assignment(assign) => {
-            println!("{}Assignment: {} {}", ind, assign.name, assign.op);
-            print_node(&assign.expr, indent + 1);
+            writeln!(out, "{}Assignment: {} {}", ind, assign.name, assign.op).unwrap();
+            print_node(&assign.expr, indent + 1, out);
         }
         wrust::ASTNode::Binary(bin) => {
-            println!("{}Binary: {}", ind, bin.op);
-            print_node(&bin.left, indent + 1);
-            print_node(&bin.right, indent + 1);
+            writeln!(out, "{}Binary: {}", ind, bin.op).unwrap();
+            print_node(&bin.left, indent + 1, out);
+            print_node(&bin.right, indent + 1, out);
         }
         wrust::ASTNode::Unary(un) => {
-            println!("{}Unary: {}", ind, un.op);
-            print_node(&un.expr, indent + 1);
+            writeln!(out, "{}Unary: {}", ind, un.op).unwrap();
+            print_node(&un.expr, indent + 1, out);
         }
         wrust::ASTNode::Call(call) => {
-            print!("{}Call: ", ind);
+            write!(out, "{}Call: ", ind).unwrap();
             if let wrust::ASTNode::Var(v) = call.name.as_ref() {
-                println!("{}", v.name);
+                writeln!(out, "{}", v.name).unwrap();
             } else {
-                println!("<complex>");
-                print_node(&call.name, indent + 1);
+                writeln!(out, "<complex>").unwrap();
+                print_node(&call.name, indent + 1, out);
             }
             for arg in &call.args {
-                print_node(arg, indent + 1);
+                print_node(arg, indent + 1, out);
             }
         }
         wrust::ASTNode::If(if_expr) => {
-            println!("{}If", ind);
-            println!("{}  condition:", ind);
-            print_node(&if_expr.condition, indent + 2);
-            println!("{}  then:", ind);
-            print_node(&if_expr.then_expr, indent + 2);
-            println!("{}  else:", ind);
-            print_node(&if_expr.else_expr, indent + 2);
+            writeln!(out, "{}If", ind).unwrap();
+            writeln!(out, "{}  condition:", ind).unwrap();
+            print_node(&if_expr.condition, indent + 2, out);
+            writeln!(out, "{}  then:", ind).unwrap();
+            print_node(&if_expr.then_expr, indent + 2, out);
+            writeln!(out, "{}  else:", ind).unwrap();
+            print_node(&if_expr.else_expr, indent + 2, out);
         }
         wrust::ASTNode::ForLoop(for_loop) => {
-            println!("{}For: {} in", ind, for_loop.var);
-            print_node(&for_loop.start, indent + 1);
-            println!("{}  to", ind);
-            print_node(&for_loop.end, indent + 1);
-            println!("{}  body:", ind);
-            print_node(&for_loop.body, indent + 2);
+            writeln!(out, "{}For: {} in", ind, for_loop.var).unwrap();
+            match &for_loop.kind {
+                wrust::ForKind::Range { start, end, step } => {
+                    print_node(start, indent + 1, out);
+                    writeln!(out, "{}  to", ind).unwrap();
+                    print_node(end, indent + 1, out);
+                    if let Some(step) = step {
+                        writeln!(out, "{}  step", ind).unwrap();
+                        print_node(step, indent + 1, out);
+                    }
+                }
+                wrust::ForKind::Each { iterable } => {
+                    print_node(iterable, indent + 1, out);
+                }
+            }
+            writeln!(out, "{}  body:", ind).unwrap();
+            print_node(&for_loop.body, indent + 2, out);
+            if let Some(else_body) = &for_loop.else_body {
+                writeln!(out, "{}  else:", ind).unwrap();
+                print_node(else_body, indent + 2, out);
+            }
+        }
+        wrust::ASTNode::Return(ret) => {
+            writeln!(out, "{}Return", ind).unwrap();
+            if let Some(expr) = &ret.expr {
+                print_node(expr, indent + 1, out);
+            }
         }
         wrust::ASTNode::Tuple(tuple) => {
-            println!("{}Tuple ({} items)", ind, tuple.items.len());
+            writeln!(out, "{}Tuple ({} items)", ind, tuple.items.len()).unwrap();
             for item in &tuple.items {
-                print_node(item, indent + 1);
+                print_node(item, indent + 1, out);
             }
         }
         wrust::ASTNode::Index(index) => {
-            println!("{}Index", ind);
-            print_node(&index.base, indent + 1);
-            println!("{}  [", ind);
-            print_node(&index.index, indent + 1);
-            println!("{}  ]", ind);
+            writeln!(out, "{}Index", ind).unwrap();
+            print_node(&index.base, indent + 1, out);
+            writeln!(out, "{}  [", ind).unwrap();
+            print_node(&index.index, indent + 1, out);
+            writeln!(out, "{}  ]", ind).unwrap();
         }
         wrust::ASTNode::StrandAccess(access) => {
-            print!("{}StrandAccess: ", ind);
+            write!(out, "{}StrandAccess: ", ind).unwrap();
             if let wrust::ASTNode::Var(base) = access.base.as_ref() {
-                if let wrust::ASTNode::Var(out) = access.out.as_ref() {
-                    println!("{}@{}", base.name, out.name);
+                if let wrust::ASTNode::Var(out_var) = access.out.as_ref() {
+                    writeln!(out, "{}@{}", base.name, out_var.name).unwrap();
                 } else {
-                    println!();
-                    print_node(&access.base, indent + 1);
-                    println!("{}  @", ind);
-                    print_node(&access.out, indent + 1);
+                    writeln!(out).unwrap();
+                    print_node(&access.base, indent + 1, out);
+                    writeln!(out, "{}  @", ind).unwrap();
+                    print_node(&access.out, indent + 1, out);
                 }
             } else {
-                println!();
-                print_node(&access.base, indent + 1);
-                println!("{}  @", ind);
-                print_node(&access.out, indent + 1);
+                writeln!(out).unwrap();
+                print_node(&access.base, indent + 1, out);
+                writeln!(out, "{}  @", ind).unwrap();
+                print_node(&access.out, indent + 1, out);
             }
         }
         wrust::ASTNode::StrandRemap(remap) => {
-            print!("{}StrandRemap: ", ind);
+            write!(out, "{}StrandRemap: ", ind).unwrap();
             if let wrust::ASTNode::Var(base) = remap.base.as_ref() {
-                println!("{}@{}", base.name, remap.strand);
+                writeln!(out, "{}@{}", base.name, remap.strand).unwrap();
             } else {
-                println!();
-                print_node(&remap.base, indent + 1);
+                writeln!(out).unwrap();
+                print_node(&remap.base, indent + 1, out);
             }
             for mapping in &remap.mappings {
-                println!("{}  mapping:", ind);
-                print_node(&mapping.axis, indent + 2);
-                println!("{}    ~", ind);
-                print_node(&mapping.expr, indent + 2);
+                writeln!(out, "{}  mapping:", ind).unwrap();
+                print_node(&mapping.axis, indent + 2, out);
+                writeln!(out, "{}    ~", ind).unwrap();
+                print_node(&mapping.expr, indent + 2, out);
             }
         }
         wrust::ASTNode::Num(num) => {
-            println!("{}Num: {}", ind, num.v);
+            writeln!(out, "{}Num: {}", ind, num.v).unwrap();
         }
         wrust::ASTNode::Str(s) => {
-            println!("{}Str: {}", ind, s.v);
+            writeln!(out, "{}Str: {}", ind, s.v).unwrap();
         }
         wrust::ASTNode::Var(v) => {
-            println!("{}Var: {}", ind, v.name);
+            writeln!(out, "{}Var: {}", ind, v.name).unwrap();
         }
         wrust::ASTNode::Me(me) => {
-            println!("{}Me: @{}", ind, me.field);
+            writeln!(out, "{}Me: @{}", ind, me.field).unwrap();
         }
         wrust::ASTNode::NamedArg(arg) => {
-            println!("{}NamedArg: {}", ind, arg.name);
-            print_node(&arg.value, indent + 1);
+            writeln!(out, "{}NamedArg: {}", ind, arg.name).unwrap();
+            print_node(&arg.value, indent + 1, out);
+        }
+        wrust::ASTNode::Pragma(pragma) => {
+            writeln!(out, "{}Pragma: #{} {}", ind, pragma.kind, pragma.args.join(" ")).unwrap();
+        }
+        wrust::ASTNode::Match(match_expr) => {
+            writeln!(out, "{}Match", ind).unwrap();
+            writeln!(out, "{}  scrutinee:", ind).unwrap();
+            print_node(&match_expr.scrutinee, indent + 2, out);
+            for arm in &match_expr.arms {
+                writeln!(out, "{}  arm {:?}:", ind, arm.pattern).unwrap();
+                print_node(&arm.body, indent + 2, out);
+            }
         }
         wrust::ASTNode::Program(_) => {
-            println!("{}Program (nested - unexpected)", ind);
+            writeln!(out, "{}Program (nested - unexpected)", ind).unwrap();
         }
     }
 }
 
-fn cmd_graph(file: PathBuf, show_order: bool, verbose: bool) -> Result<(), WeftError> {
-    let source = read_file(file)?;
-    let ast =
-        parser::parse(&source).map_err(|e| WeftError::Runtime(format!("Parse error: {}", e)))?;
+/// One `weft graph --format json` entry. A hand-picked subset of
+/// `GraphNode`'s fields (plus its position in `exec_order`), rather than
+/// `GraphNode` itself, since `GraphNode` also carries AST nodes that aren't
+/// meaningful to serialize.
+#[derive(serde::Serialize)]
+struct GraphNodeJson {
+    instance_name: String,
+    outputs: Vec<String>,
+    node_type: &'static str,
+    deps: Vec<String>,
+    required_outputs: Vec<String>,
+    contexts: Vec<String>,
+    exec_order: usize,
+}
+
+fn cmd_graph(
+    file: PathBuf,
+    show_order: bool,
+    verbose: bool,
+    format: OutputFormat,
+    metrics: Option<PathBuf>,
+) -> Result<(), WeftError> {
+    let source = read_file(file.clone())?;
+    let ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
 
     let env = Env::new(800, 600);
 
     let mut graph = wrust::runtime::render_graph::RenderGraph::new();
     let exec_order = graph.build(&ast, &env)?;
 
-    if !show_order && !verbose {
-        for node_name in &exec_order {
-            if let Some(node) = graph.get_node(node_name) {
-                let outputs: Vec<String> = node.outputs.keys().map(|s| s.to_string()).collect();
-                let node_type = match node.node_type {
-                    wrust::runtime::render_graph::NodeType::Expression => "expr",
-                    wrust::runtime::render_graph::NodeType::Spindle => "spindle",
-                    wrust::runtime::render_graph::NodeType::Builtin => "builtin",
-                };
+    if let Some(metrics_path) = metrics {
+        let key = file
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("<unknown>")
+            .to_string();
+        let report = graph_metrics(&graph, &exec_order);
+        merge_metrics_file(&metrics_path, &key, &report)?;
+    }
 
-                print!("{} <{}>", node.instance_name, outputs.join(", "));
-                print!(" ({})", node_type);
+    if format == OutputFormat::Json {
+        let nodes: Vec<GraphNodeJson> = exec_order
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node_name)| {
+                let node = graph.get_node(node_name)?;
+                Some(GraphNodeJson {
+                    instance_name: node.instance_name.clone(),
+                    outputs: node.outputs.keys().cloned().collect(),
+                    node_type: match node.node_type {
+                        wrust::runtime::render_graph::NodeType::Expression => "expr",
+                        wrust::runtime::render_graph::NodeType::Spindle => "spindle",
+                        wrust::runtime::render_graph::NodeType::Builtin => "builtin",
+                    },
+                    deps: node.deps.iter().cloned().collect(),
+                    required_outputs: node.required_outputs.iter().cloned().collect(),
+                    contexts: node.contexts.iter().map(|c| format!("{:?}", c)).collect(),
+                    exec_order: i,
+                })
+            })
+            .collect();
 
-                if !node.deps.is_empty() {
-                    let deps: Vec<String> = node.deps.iter().map(|s| s.to_string()).collect();
-                    print!(" <- {}", deps.join(", "));
-                }
+        let json = serde_json::to_string_pretty(&nodes)
+            .map_err(|e| WeftError::Runtime(format!("Failed to serialize graph: {}", e)))?;
+        println!("{}", json);
+        return Ok(());
+    }
 
-                if !node.contexts.is_empty() {
-                    let contexts: Vec<_> = node.contexts.iter().map(|c| format!("{:?}", c)).collect();
-                    print!(" [{}]", contexts.join(", "));
-                }
+    if format == OutputFormat::Dot {
+        print!("{}", render_graph_dot(&graph, &exec_order));
+        return Ok(());
+    }
 
-                println!();
-            }
-        }
+    if !show_order && !verbose {
+        print!("{}", render_graph_default(&graph, &exec_order));
     }
 
     if show_order {
@@ -337,11 +523,247 @@ fn cmd_graph(file: PathBuf, show_order: bool, verbose: bool) -> Result<(), WeftE
     Ok(())
 }
 
-fn cmd_check(file: PathBuf) -> Result<(), WeftError> {
+/// Renders the default (no `--order`, no `--verbose`) `weft graph` listing,
+/// so both `cmd_graph` (which prints it) and `cmd_test`'s `Graph`-mode
+/// snapshots (which diff it) share one rendering.
+fn render_graph_default(
+    graph: &wrust::runtime::render_graph::RenderGraph,
+    exec_order: &[String],
+) -> String {
+    let mut out = String::new();
+    for node_name in exec_order {
+        if let Some(node) = graph.get_node(node_name) {
+            let outputs: Vec<String> = node.outputs.keys().map(|s| s.to_string()).collect();
+            let node_type = match node.node_type {
+                wrust::runtime::render_graph::NodeType::Expression => "expr",
+                wrust::runtime::render_graph::NodeType::Spindle => "spindle",
+                wrust::runtime::render_graph::NodeType::Builtin => "builtin",
+            };
+
+            write!(out, "{} <{}>", node.instance_name, outputs.join(", ")).unwrap();
+            write!(out, " ({})", node_type).unwrap();
+
+            if !node.deps.is_empty() {
+                let deps: Vec<String> = node.deps.iter().map(|s| s.to_string()).collect();
+                write!(out, " <- {}", deps.join(", ")).unwrap();
+            }
+
+            if !node.contexts.is_empty() {
+                let contexts: Vec<_> = node.contexts.iter().map(|c| format!("{:?}", c)).collect();
+                write!(out, " [{}]", contexts.join(", ")).unwrap();
+            }
+
+            writeln!(out).unwrap();
+        }
+    }
+    out
+}
+
+/// Renders the `--format dot` listing for `weft graph`: one vertex per node,
+/// colored by `NodeType`, with `deps` drawn as directed edges and nodes
+/// grouped into `subgraph cluster_*` blocks by `Context` so the
+/// audio/visual/compute partitions `MetaGraph` would assign them to are
+/// visually distinct even though this CLI only has each node's own
+/// `contexts` to go on.
+fn render_graph_dot(graph: &wrust::runtime::render_graph::RenderGraph, exec_order: &[String]) -> String {
+    fn node_color(node_type: wrust::runtime::render_graph::NodeType) -> &'static str {
+        match node_type {
+            wrust::runtime::render_graph::NodeType::Expression => "lightblue",
+            wrust::runtime::render_graph::NodeType::Spindle => "lightgreen",
+            wrust::runtime::render_graph::NodeType::Builtin => "lightgoldenrod",
+        }
+    }
+
+    fn dot_id(name: &str) -> String {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    let mut clusters: std::collections::BTreeMap<String, Vec<&String>> = std::collections::BTreeMap::new();
+    let mut unclustered: Vec<&String> = Vec::new();
+
+    for node_name in exec_order {
+        if let Some(node) = graph.get_node(node_name) {
+            if node.contexts.is_empty() {
+                unclustered.push(node_name);
+            } else {
+                let mut contexts: Vec<String> =
+                    node.contexts.iter().map(|c| format!("{:?}", c)).collect();
+                contexts.sort();
+                clusters.entry(contexts.join("_")).or_default().push(node_name);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "digraph weft {{").unwrap();
+    writeln!(out, "    rankdir=LR;").unwrap();
+    writeln!(out, "    node [style=filled];").unwrap();
+    writeln!(out).unwrap();
+
+    let label_and_color = |node_name: &str| -> Option<(String, &'static str)> {
+        let node = graph.get_node(node_name)?;
+        let outputs: Vec<String> = node.outputs.keys().map(|s| s.to_string()).collect();
+        Some((
+            format!("{}<{}>", node.instance_name, outputs.join(", ")),
+            node_color(node.node_type),
+        ))
+    };
+
+    for (i, (context_label, node_names)) in clusters.iter().enumerate() {
+        writeln!(out, "    subgraph cluster_{} {{", i).unwrap();
+        writeln!(out, "        label = \"{}\";", context_label).unwrap();
+        writeln!(out, "        style = dashed;").unwrap();
+        for node_name in node_names {
+            if let Some((label, color)) = label_and_color(node_name) {
+                writeln!(
+                    out,
+                    "        {} [label=\"{}\", fillcolor={}];",
+                    dot_id(node_name),
+                    label,
+                    color
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+
+    for node_name in &unclustered {
+        if let Some((label, color)) = label_and_color(node_name) {
+            writeln!(
+                out,
+                "    {} [label=\"{}\", fillcolor={}];",
+                dot_id(node_name),
+                label,
+                color
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(out).unwrap();
+    for node_name in exec_order {
+        if let Some(node) = graph.get_node(node_name) {
+            for dep in &node.deps {
+                writeln!(out, "    {} -> {};", dot_id(dep), dot_id(node_name)).unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Structural complexity metrics for a built `RenderGraph`, written by
+/// `weft graph --metrics` so a CI loop can track graph-complexity
+/// regressions across a suite of WEFT programs over time.
+#[derive(serde::Serialize)]
+struct GraphMetrics {
+    total_nodes: usize,
+    context_counts: std::collections::BTreeMap<String, usize>,
+    node_type_counts: std::collections::BTreeMap<String, usize>,
+    max_dependency_depth: usize,
+    /// `deps.len()` (fan-in) for each node, keyed by that count, so
+    /// `fan_in_distribution["3"]` is how many nodes have exactly 3
+    /// dependencies.
+    fan_in_distribution: std::collections::BTreeMap<usize, usize>,
+    /// Like `fan_in_distribution`, but keyed by how many *other* nodes
+    /// depend on a given node.
+    fan_out_distribution: std::collections::BTreeMap<usize, usize>,
+}
+
+fn graph_metrics(
+    graph: &wrust::runtime::render_graph::RenderGraph,
+    exec_order: &[String],
+) -> GraphMetrics {
+    let mut context_counts = std::collections::BTreeMap::new();
+    let mut node_type_counts = std::collections::BTreeMap::new();
+    let mut fan_in_distribution: std::collections::BTreeMap<usize, usize> =
+        std::collections::BTreeMap::new();
+    let mut fan_out_counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    let mut depth: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    let mut max_dependency_depth = 0;
+
+    for node_name in exec_order {
+        let Some(node) = graph.get_node(node_name) else {
+            continue;
+        };
+
+        for context in &node.contexts {
+            *context_counts.entry(format!("{:?}", context)).or_insert(0) += 1;
+        }
+
+        let node_type = match node.node_type {
+            wrust::runtime::render_graph::NodeType::Expression => "expr",
+            wrust::runtime::render_graph::NodeType::Spindle => "spindle",
+            wrust::runtime::render_graph::NodeType::Builtin => "builtin",
+        };
+        *node_type_counts.entry(node_type.to_string()).or_insert(0) += 1;
+
+        *fan_in_distribution.entry(node.deps.len()).or_insert(0) += 1;
+        for dep in &node.deps {
+            *fan_out_counts.entry(dep.as_str()).or_insert(0) += 1;
+        }
+
+        let node_depth = 1 + node
+            .deps
+            .iter()
+            .filter_map(|dep| depth.get(dep.as_str()))
+            .copied()
+            .max()
+            .unwrap_or(0);
+        depth.insert(node_name.as_str(), node_depth);
+        max_dependency_depth = max_dependency_depth.max(node_depth);
+    }
+
+    // Nodes with no dependents at all never show up in `fan_out_counts`,
+    // so they need to be counted into the `0` bucket explicitly.
+    let mut fan_out_distribution: std::collections::BTreeMap<usize, usize> =
+        std::collections::BTreeMap::new();
+    for node_name in exec_order {
+        let count = fan_out_counts.get(node_name.as_str()).copied().unwrap_or(0);
+        *fan_out_distribution.entry(count).or_insert(0) += 1;
+    }
+
+    GraphMetrics {
+        total_nodes: exec_order.len(),
+        context_counts,
+        node_type_counts,
+        max_dependency_depth,
+        fan_in_distribution,
+        fan_out_distribution,
+    }
+}
+
+/// Writes `report` into the JSON object at `path` under `key`, deep-merging
+/// by top-level key with whatever object is already there (or starting a
+/// fresh one if `path` doesn't exist yet or isn't valid JSON) rather than
+/// overwriting the whole file -- so repeated `weft graph --metrics`
+/// invocations across a suite of programs accumulate into one combined
+/// artifact instead of clobbering each other.
+fn merge_metrics_file(path: &Path, key: &str, report: &GraphMetrics) -> Result<(), WeftError> {
+    let mut root: serde_json::Map<String, serde_json::Value> = fs::read_to_string(path)
+        .ok()
+        .and_then(|existing| serde_json::from_str::<serde_json::Value>(&existing).ok())
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default();
+
+    let entry = serde_json::to_value(report)
+        .map_err(|e| WeftError::Runtime(format!("Failed to serialize graph metrics: {}", e)))?;
+    root.insert(key.to_string(), entry);
+
+    let json = serde_json::to_string_pretty(&root)
+        .map_err(|e| WeftError::Runtime(format!("Failed to serialize graph metrics: {}", e)))?;
+    fs::write(path, json)
+        .map_err(|e| WeftError::Runtime(format!("Failed to write {:?}: {}", path, e)))?;
+
+    Ok(())
+}
+
+fn cmd_check(file: PathBuf, fix: bool) -> Result<(), WeftError> {
     let source = read_file(file.clone())?;
 
-    let ast =
-        parser::parse(&source).map_err(|e| WeftError::Runtime(format!("Parse error: {}", e)))?;
+    let ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
 
     println!("✓ Syntax is valid");
     println!("✓ Found {} statement(s)", ast.statements.len());
@@ -353,19 +775,76 @@ fn cmd_check(file: PathBuf) -> Result<(), WeftError> {
     println!("✓ Dependency graph is valid");
     println!("✓ Execution order: {} nodes", exec_order.len());
 
+    let diagnostics = wrust::diagnostics::check(&ast);
+
+    if diagnostics.is_empty() {
+        println!("\n{:?} passes all checks!", file);
+        return Ok(());
+    }
+
+    println!();
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic.render(&source));
+    }
+
+    let fixable = diagnostics.iter().filter(|d| d.fix.is_some()).count();
+    if fix {
+        if fixable > 0 {
+            let fixed = wrust::diagnostics::apply_fixes(&source, &diagnostics);
+            let tmp_path = file.with_extension("tmp");
+            fs::write(&tmp_path, &fixed).map_err(|e| {
+                WeftError::Runtime(format!("Failed to write {:?}: {}", tmp_path, e))
+            })?;
+            fs::rename(&tmp_path, &file)
+                .map_err(|e| WeftError::Runtime(format!("Failed to write {:?}: {}", file, e)))?;
+            println!("\nApplied {} fix(es) to {:?}", fixable, file);
+        } else {
+            println!("\nNo fixable diagnostics.");
+        }
+    }
+
+    if wrust::diagnostics::has_error(&diagnostics) {
+        return Err(WeftError::Runtime(format!(
+            "{:?} failed {} check(s)",
+            file,
+            diagnostics.len()
+        )));
+    }
+
     println!("\n{:?} passes all checks!", file);
 
     Ok(())
 }
 
-fn cmd_info(file: PathBuf) -> Result<(), WeftError> {
-    let source = read_file(file.clone())?;
-    let ast =
-        parser::parse(&source).map_err(|e| WeftError::Runtime(format!("Parse error: {}", e)))?;
+#[derive(serde::Serialize)]
+struct InfoGraphJson {
+    computation_nodes: usize,
+    visual_nodes: usize,
+    audio_nodes: usize,
+    compute_nodes: usize,
+}
 
-    println!("File: {:?}", file);
-    println!("Size: {} bytes", source.len());
-    println!();
+#[derive(serde::Serialize)]
+struct InfoJson {
+    file: String,
+    size_bytes: usize,
+    statements: usize,
+    spindle_defs: usize,
+    instance_bindings: usize,
+    backends: usize,
+    assignments: usize,
+    graph: Option<InfoGraphJson>,
+}
+
+fn cmd_info(file: PathBuf, format: OutputFormat) -> Result<(), WeftError> {
+    if format == OutputFormat::Dot {
+        return Err(WeftError::Runtime(
+            "`--format dot` is only supported by `weft graph`".to_string(),
+        ));
+    }
+
+    let source = read_file(file.clone())?;
+    let ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
 
     let mut spindle_defs = 0;
     let mut instance_bindings = 0;
@@ -382,20 +861,9 @@ fn cmd_info(file: PathBuf) -> Result<(), WeftError> {
         }
     }
 
-    println!("Statements:");
-    println!("  Total: {}", ast.statements.len());
-    println!("  Spindle definitions: {}", spindle_defs);
-    println!("  Instance bindings: {}", instance_bindings);
-    println!("  Backend outputs: {}", backends);
-    println!("  Environment assignments: {}", assignments);
-
     let env = Env::new(800, 600);
     let mut graph = wrust::runtime::render_graph::RenderGraph::new();
-    if let Ok(exec_order) = graph.build(&ast, &env) {
-        println!();
-        println!("Dependency Graph:");
-        println!("  Computation nodes: {}", exec_order.len());
-
+    let graph_info = graph.build(&ast, &env).ok().map(|exec_order| {
         let mut visual_nodes = 0;
         let mut audio_nodes = 0;
         let mut compute_nodes = 0;
@@ -423,18 +891,111 @@ fn cmd_info(file: PathBuf) -> Result<(), WeftError> {
             }
         }
 
-        println!("  Visual context nodes: {}", visual_nodes);
-        println!("  Audio context nodes: {}", audio_nodes);
-        println!("  Compute context nodes: {}", compute_nodes);
+        InfoGraphJson {
+            computation_nodes: exec_order.len(),
+            visual_nodes,
+            audio_nodes,
+            compute_nodes,
+        }
+    });
+
+    if format == OutputFormat::Json {
+        let info = InfoJson {
+            file: format!("{:?}", file),
+            size_bytes: source.len(),
+            statements: ast.statements.len(),
+            spindle_defs,
+            instance_bindings,
+            backends,
+            assignments,
+            graph: graph_info,
+        };
+        let json = serde_json::to_string_pretty(&info)
+            .map_err(|e| WeftError::Runtime(format!("Failed to serialize info: {}", e)))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("File: {:?}", file);
+    println!("Size: {} bytes", source.len());
+    println!();
+
+    println!("Statements:");
+    println!("  Total: {}", ast.statements.len());
+    println!("  Spindle definitions: {}", spindle_defs);
+    println!("  Instance bindings: {}", instance_bindings);
+    println!("  Backend outputs: {}", backends);
+    println!("  Environment assignments: {}", assignments);
+
+    if let Some(graph_info) = graph_info {
+        println!();
+        println!("Dependency Graph:");
+        println!("  Computation nodes: {}", graph_info.computation_nodes);
+        println!("  Visual context nodes: {}", graph_info.visual_nodes);
+        println!("  Audio context nodes: {}", graph_info.audio_nodes);
+        println!("  Compute context nodes: {}", graph_info.compute_nodes);
     }
 
     Ok(())
 }
 
-fn cmd_run(file: PathBuf, width: u32, height: u32, fps: f64) -> Result<(), WeftError> {
+fn cmd_fmt(file: PathBuf, write: bool) -> Result<(), WeftError> {
     let source = read_file(file.clone())?;
-    let _ast =
-        parser::parse(&source).map_err(|e| WeftError::Runtime(format!("Parse error: {}", e)))?;
+    let ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
+
+    let formatted = wrust::print::format_program(&ast);
+
+    if write {
+        fs::write(&file, &formatted)
+            .map_err(|e| WeftError::Runtime(format!("Failed to write {:?}: {}", file, e)))?;
+    } else {
+        println!("{}", formatted);
+    }
+
+    Ok(())
+}
+
+/// Parses one `--set KEY=VALUE[:TYPE]` flag into the key it overrides and
+/// the typed value to store for it, converting `VALUE` through `TYPE`
+/// (`Conversion::Bytes`, taken as-is, when no `:TYPE` suffix is present).
+/// The `:timestamp(FMT)` suffix is matched before a bare `:TYPE` one since
+/// `FMT` itself may contain colons (e.g. `timestamp(%H:%M:%S)`).
+fn parse_set_flag(raw: &str) -> Result<(String, wrust::runtime::env::EnvValue), WeftError> {
+    let (key, rest) = raw.split_once('=').ok_or_else(|| {
+        WeftError::Runtime(format!(
+            "`--set {}` is missing the `=` between KEY and VALUE",
+            raw
+        ))
+    })?;
+
+    let (value, conversion) = if let Some(idx) = rest.rfind(":timestamp(") {
+        (&rest[..idx], &rest[idx + 1..])
+    } else if let Some(idx) = rest.rfind(':') {
+        (&rest[..idx], &rest[idx + 1..])
+    } else {
+        (rest, "bytes")
+    };
+
+    let conversion = conversion
+        .parse::<wrust::runtime::env::Conversion>()
+        .map_err(|e| WeftError::Runtime(format!("`--set {}`: {}", raw, e)))?;
+
+    let typed = conversion.convert(value).map_err(|e| {
+        WeftError::Runtime(format!("`--set {}`: value `{}`: {}", key, value, e))
+    })?;
+
+    Ok((key.to_string(), typed))
+}
+
+fn cmd_run(
+    file: PathBuf,
+    width: u32,
+    height: u32,
+    fps: f64,
+    set: Vec<String>,
+) -> Result<(), WeftError> {
+    let source = read_file(file.clone())?;
+    let _ast = parser::parse(&source).context(format!("failed to load {:?}", file))?;
 
     println!("Running WEFT program: {:?}", file);
     println!("Canvas: {}x{}, Target FPS: {}", width, height, fps);
@@ -443,6 +1004,21 @@ fn cmd_run(file: PathBuf, width: u32, height: u32, fps: f64) -> Result<(), WeftE
     let mut _env = Env::new(width, height);
     _env.target_fps = fps;
 
+    for flag in &set {
+        let (key, value) = parse_set_flag(flag)?;
+        _env.set_override(key, value);
+    }
+
+    if !_env.overrides.is_empty() {
+        println!("Overrides:");
+        let mut keys: Vec<&String> = _env.overrides.keys().collect();
+        keys.sort();
+        for key in keys {
+            println!("  {} = {:?}", key, _env.overrides[key]);
+        }
+        println!();
+    }
+
     println!("⚠ Backend implementations not yet available");
     println!("The program parsed successfully but cannot execute yet.");
     println!();
@@ -453,3 +1029,211 @@ fn cmd_run(file: PathBuf, width: u32, height: u32, fps: f64) -> Result<(), WeftE
 
     Ok(())
 }
+
+/// What stage of the pipeline a `.weft` fixture exercises, selected per-file
+/// by a `//@ mode: ...` header comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Asserts `parser::parse` returns `Err`.
+    ParseFail,
+    /// Asserts parsing succeeds but the dependency graph build or the
+    /// diagnostics pass (see `wrust::diagnostics`) reports an error.
+    CheckFail,
+    /// Diffs `print_ast`'s pretty-printed tree against a `.expected`
+    /// snapshot.
+    Parse,
+    /// Diffs `render_graph_default`'s listing against a `.expected`
+    /// snapshot.
+    Graph,
+    /// Asserts parsing and the dependency graph build both succeed.
+    RunPass,
+}
+
+impl FromStr for Mode {
+    type Err = WeftError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse-fail" => Ok(Mode::ParseFail),
+            "check-fail" => Ok(Mode::CheckFail),
+            "parse" => Ok(Mode::Parse),
+            "graph" => Ok(Mode::Graph),
+            "run-pass" => Ok(Mode::RunPass),
+            other => Err(WeftError::Runtime(format!("unknown test mode `{}`", other))),
+        }
+    }
+}
+
+/// The `//@ mode: ...` and optional `//@ error: ...` header comments found
+/// anywhere in a fixture, compiletest-style.
+struct FixtureAnnotations {
+    mode: Mode,
+    expected_error: Option<String>,
+}
+
+fn parse_annotations(source: &str, path: &Path) -> Result<FixtureAnnotations, WeftError> {
+    let mut mode = None;
+    let mut expected_error = None;
+
+    for line in source.lines() {
+        let Some(rest) = line.trim().strip_prefix("//@") else {
+            continue;
+        };
+        let rest = rest.trim();
+
+        if let Some(value) = rest.strip_prefix("mode:") {
+            mode = Some(value.trim().parse::<Mode>()?);
+        } else if let Some(value) = rest.strip_prefix("error:") {
+            expected_error = Some(value.trim().to_string());
+        }
+    }
+
+    let mode = mode.ok_or_else(|| {
+        WeftError::Runtime(format!("{:?} has no `//@ mode: ...` annotation", path))
+    })?;
+
+    Ok(FixtureAnnotations {
+        mode,
+        expected_error,
+    })
+}
+
+/// If `expected` is set, asserts `actual` contains it as a substring.
+fn check_expected_error(actual: &str, expected: &Option<String>) -> Result<(), String> {
+    match expected {
+        Some(expected) if !actual.contains(expected.as_str()) => Err(format!(
+            "error did not contain the expected substring `{}`:\n{}",
+            expected, actual
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Compares `actual` against `fixture`'s sibling `.expected` snapshot,
+/// or overwrites it when `bless` is set.
+fn compare_snapshot(fixture: &Path, actual: &str, bless: bool) -> Result<(), String> {
+    let snapshot_path = fixture.with_extension("expected");
+
+    if bless {
+        fs::write(&snapshot_path, actual)
+            .map_err(|e| format!("failed to write snapshot {:?}: {}", snapshot_path, e))?;
+        return Ok(());
+    }
+
+    let expected = fs::read_to_string(&snapshot_path).map_err(|e| {
+        format!(
+            "no snapshot at {:?} ({}); rerun with --bless to create one",
+            snapshot_path, e
+        )
+    })?;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "output does not match {:?}\n--- expected ---\n{}--- actual ---\n{}",
+            snapshot_path, expected, actual
+        ))
+    }
+}
+
+/// Runs a single `.weft` fixture according to its annotated `Mode`.
+fn run_fixture(fixture: &Path, bless: bool) -> Result<(), String> {
+    let source = fs::read_to_string(fixture).map_err(|e| format!("failed to read: {}", e))?;
+    let annotations = parse_annotations(&source, fixture).map_err(|e| e.to_string())?;
+
+    match annotations.mode {
+        Mode::ParseFail => match parser::parse(&source) {
+            Ok(_) => Err("expected parsing to fail, but it succeeded".to_string()),
+            Err(e) => check_expected_error(&e.to_string(), &annotations.expected_error),
+        },
+
+        Mode::CheckFail => {
+            let ast = parser::parse(&source)
+                .map_err(|e| format!("expected check to fail, but parsing failed first: {}", e))?;
+            let env = Env::new(800, 600);
+            let mut graph = wrust::runtime::render_graph::RenderGraph::new();
+
+            match graph.build(&ast, &env) {
+                Err(e) => check_expected_error(&e.to_string(), &annotations.expected_error),
+                Ok(_) => {
+                    let diagnostics = wrust::diagnostics::check(&ast);
+                    if !wrust::diagnostics::has_error(&diagnostics) {
+                        return Err("expected check to fail, but it passed".to_string());
+                    }
+                    let messages: Vec<&str> =
+                        diagnostics.iter().map(|d| d.message.as_str()).collect();
+                    check_expected_error(&messages.join("\n"), &annotations.expected_error)
+                }
+            }
+        }
+
+        Mode::Parse => {
+            let ast = parser::parse(&source).map_err(|e| format!("parse failed: {}", e))?;
+            let mut actual = String::new();
+            print_ast(&ast, 0, &mut actual);
+            compare_snapshot(fixture, &actual, bless)
+        }
+
+        Mode::Graph => {
+            let ast = parser::parse(&source).map_err(|e| format!("parse failed: {}", e))?;
+            let env = Env::new(800, 600);
+            let mut graph = wrust::runtime::render_graph::RenderGraph::new();
+            let exec_order = graph
+                .build(&ast, &env)
+                .map_err(|e| format!("graph build failed: {}", e))?;
+            let actual = render_graph_default(&graph, &exec_order);
+            compare_snapshot(fixture, &actual, bless)
+        }
+
+        Mode::RunPass => {
+            let ast = parser::parse(&source).map_err(|e| format!("parse failed: {}", e))?;
+            let env = Env::new(800, 600);
+            let mut graph = wrust::runtime::render_graph::RenderGraph::new();
+            graph
+                .build(&ast, &env)
+                .map_err(|e| format!("graph build failed: {}", e))?;
+            Ok(())
+        }
+    }
+}
+
+/// Runs every `.weft` fixture directly inside `dir` and prints a
+/// compiletest-style pass/fail summary.
+fn cmd_test(dir: PathBuf, bless: bool) -> Result<(), WeftError> {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| WeftError::Runtime(format!("Failed to read directory {:?}: {}", dir, e)))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("weft"))
+        .collect();
+    fixtures.sort();
+
+    if fixtures.is_empty() {
+        println!("No .weft fixtures found in {:?}", dir);
+        return Ok(());
+    }
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for fixture in &fixtures {
+        match run_fixture(fixture, bless) {
+            Ok(()) => {
+                println!("✓ {:?}", fixture);
+                passed += 1;
+            }
+            Err(reason) => {
+                println!("✗ {:?}: {}", fixture, reason);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed", passed, failed);
+
+    if failed > 0 {
+        return Err(WeftError::Runtime(format!("{} fixture(s) failed", failed)));
+    }
+
+    Ok(())
+}
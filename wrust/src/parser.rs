@@ -1,3 +1,6 @@
+use crate::symbol::{self, Symbol};
+use crate::utils::error::{ParseError, WeftError};
+use crate::utils::Result;
 use crate::{ast::*, backend};
 use pest::iterators::Pair;
 use pest::Parser;
@@ -7,173 +10,395 @@ use pest_derive::Parser;
 #[grammar = "weft.pest"]
 pub struct WeftParser;
 
-pub fn parse(source: &str) -> Result<Program, pest::error::Error<Rule>> {
-    let pairs = WeftParser::parse(Rule::program, source)?;
+/// File-local parse configuration, overridable mid-file by pragma
+/// statements (`#strict on`, `#backend gpu`, ...) for the remainder of
+/// the program -- see `apply_pragma`.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Rejects unrecognized pragmas instead of silently letting runtime
+    /// sort them out.
+    pub strict: bool,
+    /// Lets experimental grammar constructs parse. No such construct
+    /// gates on this yet; it exists for pragmas/callers to set ahead of
+    /// the first one that does.
+    pub allow_experimental: bool,
+    /// Backend named by a `#backend NAME` pragma. Only recorded: nothing
+    /// reads this field, so a bare `render(...)`/`play(...)` targets
+    /// whatever the backend registry picks whether or not it is set.
+    pub default_backend: Option<String>,
+}
+
+pub fn parse(source: &str) -> Result<Program> {
+    parse_with_options(source, ParseOptions::default())
+}
+
+pub fn parse_with_options(source: &str, options: ParseOptions) -> Result<Program> {
+    let pairs = WeftParser::parse(Rule::program, source).map_err(ParseError::from_pest)?;
     let program_pair = pairs.into_iter().next().unwrap();
 
-    Ok(build_program(program_pair))
+    build_program(program_pair, options)
 }
 
-fn build_program(pair: Pair<Rule>) -> Program {
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let s = pair.as_span();
+    Span::new(s.start(), s.end(), None)
+}
+
+fn build_program(pair: Pair<Rule>, mut options: ParseOptions) -> Result<Program> {
     let mut statements = Vec::new();
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::statement => {
                 let stmt_pair = inner_pair.into_inner().next().unwrap();
-                statements.push(build_statement(stmt_pair));
+                statements.push(build_statement(stmt_pair, &mut options)?);
             }
             Rule::EOI => {}
-            _ => unreachable!(),
+            other => {
+                return Err(WeftError::Located {
+                    span: span_of(&inner_pair),
+                    message: format!("unexpected top-level rule: {:?}", other),
+                });
+            }
         }
     }
-    Program { statements }
+    Ok(Program { statements })
 }
 
-fn build_statement(pair: Pair<Rule>) -> ASTNode {
+fn build_statement(pair: Pair<Rule>, options: &mut ParseOptions) -> Result<ASTNode> {
+    let span = span_of(&pair);
     match pair.as_rule() {
         Rule::spindle_def => build_spindle_def(pair),
         Rule::env_assignment => build_env_assignment(pair),
         Rule::instance_binding => build_instance_binding(pair),
         Rule::assignment => build_assignment(pair),
         Rule::backend_expr => build_output_statement(pair),
-        // Rule::pragma => build_pragma(pair),
-        _ => unreachable!("Unexpected statement rule: {:?}", pair.as_rule()),
+        Rule::pragma => build_pragma(pair, options),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected statement rule: {:?}", other),
+        }),
     }
 }
 
-fn build_assignment(pair: Pair<Rule>) -> ASTNode {
+fn build_assignment(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
 
-    let name = inner.next().unwrap().as_str().to_string();
+    let name = symbol::intern(inner.next().unwrap().as_str());
     let op = inner.next().unwrap().as_str().to_string();
-    let expr = build_expr(inner.next().unwrap());
+    let expr = build_expr(inner.next().unwrap())?;
 
-    ASTNode::Assignment(AssignmentExpr {
+    Ok(ASTNode::Assignment(AssignmentExpr {
         name,
         op,
         expr: Box::new(expr),
         is_output: false,
-    })
+        span,
+    }))
 }
 
-fn build_expr(pair: Pair<Rule>) -> ASTNode {
+fn build_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let inner = pair.into_inner().next().unwrap();
 
     match inner.as_rule() {
         Rule::if_expr => build_if_expr(inner),
-        Rule::logical_expr => build_logical_expr(inner),
-        _ => unreachable!(),
+        Rule::match_expr => build_match_expr(inner),
+        Rule::pipe_expr => build_pipe_expr(inner),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected expr rule: {:?}", other),
+        }),
     }
 }
 
-fn build_logical_expr(pair: Pair<Rule>) -> ASTNode {
+/// `weft.pest` would need `pipe_expr` sitting above every binary-operator
+/// tier, along these lines (not present in this snapshot):
+///   pipe_expr = { logical_expr ~ pipe_stage* }
+///   pipe_stage = { "|>" ~ ident ~ ("(" ~ expr_list? ~ ")")? }
+///
+/// `|>` binds looser than every binary operator this way: `a + b |> f`
+/// parses as `(a + b) |> f`, matching the usual left-to-right pipeline
+/// reading (pipe the arithmetic's result into `f`), and stages left-fold
+/// like any other left-associative tier: `a |> f |> g` is `g(f(a))`.
+fn build_pipe_expr(pair: Pair<Rule>) -> Result<ASTNode> {
     let mut inner = pair.into_inner();
+    let mut base = build_logical_expr(inner.next().unwrap())?;
 
-    let mut left = build_comparison_expr(inner.next().unwrap());
+    for stage_pair in inner {
+        base = build_pipe_stage(base, stage_pair)?;
+    }
+
+    Ok(base)
+}
 
-    while let Some(op_pair) = inner.next() {
+fn build_pipe_stage(base: ASTNode, pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let name_pair = inner.next().unwrap();
+    let name = ASTNode::Var(VarExpr {
+        name: symbol::intern(name_pair.as_str()),
+        span: span_of(&name_pair),
+    });
+
+    let mut args = vec![base];
+    if let Some(args_pair) = inner.next() {
+        args.extend(build_expr_list(args_pair)?);
+    }
+
+    Ok(ASTNode::Call(CallExpr {
+        name: Box::new(name),
+        args,
+        span,
+    }))
+}
+
+/// Binding power (left, right) for every binary operator — the single
+/// source of truth for precedence. Adding an operator (modulo, a new
+/// comparison, ...) means adding one entry here, not threading a new
+/// tier through `build_logical_expr` → ... → `build_power`. Operators at
+/// the same tier share a left power; a left-associative operator's right
+/// power is `left + 1` (so a repeated use folds left, as `fold_left_assoc`
+/// below does); `^`, the one right-associative operator, has equal left
+/// and right power, matching the direct right-recursion `build_power`
+/// already does.
+pub(crate) fn binding_power(op: &str) -> (u8, u8) {
+    match op {
+        "and" | "or" => (1, 2),
+        ">>" | "<<" | "==" | "!=" | ">=" | "<=" => (2, 3),
+        "+" | "-" => (3, 4),
+        "*" | "/" | "%" => (4, 5),
+        "^" => (5, 5),
+        other => unreachable!("unknown binary operator {:?}", other),
+    }
+}
+
+/// Folds `first` together with zero or more trailing `(op, operand)`
+/// pairs into a left-associated `Binary` chain — the one routine every
+/// left-associative precedence tier (`logical_expr`, `comparison_expr`,
+/// `arith_expr`, `term`) delegates to instead of hand-rolling its own
+/// `while let` loop. `next` builds one operand at the tier below.
+fn fold_left_assoc(
+    span: &Span,
+    first: ASTNode,
+    mut rest: impl Iterator<Item = Pair<Rule>>,
+    next: impl Fn(Pair<Rule>) -> Result<ASTNode>,
+) -> Result<ASTNode> {
+    let mut left = first;
+    while let Some(op_pair) = rest.next() {
         let op = op_pair.as_str().to_string();
-        let right = build_comparison_expr(inner.next().unwrap());
+        debug_assert!(
+            binding_power(&op).0 > 0,
+            "unrecognized operator {:?} in a left-associative chain",
+            op
+        );
+        let right = next(rest.next().unwrap())?;
 
         left = ASTNode::Binary(BinaryExpr {
             op,
             left: Box::new(left),
             right: Box::new(right),
+            span: span.clone(),
         });
     }
 
-    left
+    Ok(left)
 }
 
-fn build_spindle_def(pair: Pair<Rule>) -> ASTNode {
+fn build_logical_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let name = inner.next().unwrap().as_str().to_string();
+    let first = build_comparison_expr(inner.next().unwrap())?;
+    fold_left_assoc(&span, first, inner, build_comparison_expr)
+}
+
+fn build_spindle_def(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let name = symbol::intern(inner.next().unwrap().as_str());
     let inputs = build_ident_list(inner.next().unwrap());
-    let outputs = build_output_spec(inner.next().unwrap());
-    let body = Box::new(build_block(inner.next().unwrap()));
+    let outputs: Vec<Symbol> =
+        build_output_spec(inner.next().unwrap()).iter().map(|s| symbol::intern(s)).collect();
+    let body = Box::new(build_block(inner.next().unwrap())?);
 
-    ASTNode::SpindleDef(SpindleDef {
+    Ok(ASTNode::SpindleDef(SpindleDef {
         name,
         inputs,
         outputs,
         body,
-    })
+        span,
+    }))
 }
 
-fn build_block(pair: Pair<Rule>) -> ASTNode {
+fn build_block(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut body = Vec::new();
     for stmt_pair in pair.into_inner() {
-        body.push(build_block_statement(stmt_pair));
+        body.push(build_block_statement(stmt_pair)?);
     }
 
-    ASTNode::Block(BlockExpr { body })
+    Ok(ASTNode::Block(BlockExpr { body, span }))
 }
 
-fn build_block_statement(pair: Pair<Rule>) -> ASTNode {
+fn build_block_statement(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let inner = pair.into_inner().next().unwrap();
     match inner.as_rule() {
         Rule::output_assignment => build_output_assignment(inner),
         Rule::assignment => build_assignment(inner),
         Rule::for_loop => build_for_loop(inner),
         Rule::if_expr => build_if_expr(inner),
-        _ => unreachable!(),
+        Rule::match_expr => build_match_expr(inner),
+        Rule::return_stmt => build_return(inner),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected block statement rule: {:?}", other),
+        }),
     }
 }
 
-fn build_output_assignment(pair: Pair<Rule>) -> ASTNode {
+/// `weft.pest` would need a `return_stmt` rule along these lines (not
+/// present in this snapshot):
+///   return_stmt = { "return" ~ expr? }
+fn build_return(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let expr = pair.into_inner().next().map(build_expr).transpose()?.map(Box::new);
+
+    Ok(ASTNode::Return(ReturnExpr { expr, span }))
+}
+
+fn build_output_assignment(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let name = inner.next().unwrap().as_str().to_string();
-    let expr = Box::new(build_expr(inner.next().unwrap()));
+    let name = symbol::intern(inner.next().unwrap().as_str());
+    let expr = Box::new(build_expr(inner.next().unwrap())?);
 
-    ASTNode::Assignment(AssignmentExpr {
+    Ok(ASTNode::Assignment(AssignmentExpr {
         name,
         op: "=".to_string(),
         expr,
         is_output: true,
-    })
+        span,
+    }))
 }
 
-fn build_for_loop(pair: Pair<Rule>) -> ASTNode {
+/// `weft.pest` would need a `range_clause` and trailing `else` clause
+/// added to `for_loop` along these lines (not present in this snapshot):
+///   for_loop = { "for" ~ ident ~ "in" ~ (range_clause | expr) ~ block ~ ("else" ~ block)? }
+///   range_clause = { "(" ~ expr ~ "to" ~ expr ~ ("step" ~ expr)? ~ ")" }
+///
+/// The loop source is either a `range_clause` (counting `start` to `end`)
+/// or a bare `expr` naming the collection to walk element-by-element
+/// (`Each`).
+fn build_for_loop(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
     let var = inner.next().unwrap().as_str().to_string();
-    let start = Box::new(build_expr(inner.next().unwrap()));
-    let end = Box::new(build_expr(inner.next().unwrap()));
-    let body = Box::new(build_block(inner.next().unwrap()));
+    let source = inner.next().unwrap();
+    let kind = match source.as_rule() {
+        Rule::range_clause => build_range_clause(source)?,
+        _ => ForKind::Each {
+            iterable: Box::new(build_expr(source)?),
+        },
+    };
+
+    if let ForKind::Range { start, end, step: Some(step) } = &kind {
+        validate_step_direction(start, end, step)?;
+    }
+
+    let body = Box::new(build_block(inner.next().unwrap())?);
+
+    let else_body = inner
+        .next()
+        .map(build_block)
+        .transpose()?
+        .map(Box::new);
 
-    ASTNode::ForLoop(ForLoopExpr {
+    Ok(ASTNode::ForLoop(ForLoopExpr {
         var,
-        start,
-        end,
+        kind,
+        else_body,
         body,
-    })
+        span,
+    }))
+}
+
+fn build_range_clause(pair: Pair<Rule>) -> Result<ForKind> {
+    let mut inner = pair.into_inner();
+    let start = Box::new(build_expr(inner.next().unwrap())?);
+    let end = Box::new(build_expr(inner.next().unwrap())?);
+    let step = inner.next().map(build_expr).transpose()?.map(Box::new);
+
+    Ok(ForKind::Range { start, end, step })
+}
+
+/// Reads a constant numeric value out of a literal (`5`, `-5`), or `None`
+/// if `node` isn't one -- this check only catches the common case of a
+/// literal step/bound written directly in the loop header.
+fn literal_num(node: &ASTNode) -> Option<f64> {
+    match node {
+        ASTNode::Num(n) => Some(n.v),
+        ASTNode::Unary(u) if u.op == "-" => literal_num(&u.expr).map(|v| -v),
+        _ => None,
+    }
+}
+
+/// Best-effort check that a literal `step` points the same way as the
+/// `start..end` range does (e.g. catches `for i in (0 to 10 step -1)`).
+/// Silently passes when `start`, `end`, or `step` isn't a literal number,
+/// since the direction can't be known until runtime.
+fn validate_step_direction(start: &ASTNode, end: &ASTNode, step_node: &ASTNode) -> Result<()> {
+    if let (Some(start), Some(end), Some(step)) =
+        (literal_num(start), literal_num(end), literal_num(step_node))
+    {
+        let ascending = end >= start;
+        if (ascending && step < 0.0) || (!ascending && step > 0.0) {
+            return Err(WeftError::Located {
+                span: step_node.span().cloned().unwrap_or_default(),
+                message: format!(
+                    "step {} does not match the range direction ({} to {})",
+                    step, start, end
+                ),
+            });
+        }
+    }
+
+    Ok(())
 }
 
-fn build_env_assignment(pair: Pair<Rule>) -> ASTNode {
+fn build_env_assignment(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let ident = inner.next().unwrap().as_str().to_string();
-    let expr = Box::new(build_expr(inner.next().unwrap()));
+    let ident = symbol::intern(inner.next().unwrap().as_str());
+    let expr = Box::new(build_expr(inner.next().unwrap())?);
 
-    ASTNode::Assignment(AssignmentExpr {
+    Ok(ASTNode::Assignment(AssignmentExpr {
         name: ident,
         op: "=".to_string(),
         expr,
         is_output: false,
-    })
+        span,
+    }))
 }
 
-fn build_instance_binding(pair: Pair<Rule>) -> ASTNode {
+fn build_instance_binding(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let inner = pair.into_inner().next().unwrap();
 
     match inner.as_rule() {
         Rule::multi_spindle_call => build_multi_spindle_call(inner),
         Rule::spindle_call => build_spindle_call(inner),
         Rule::direct_bind => build_direct_bind(inner),
-        _ => unreachable!(),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected instance binding rule: {:?}", other),
+        }),
     }
 }
 
-fn build_multi_spindle_call(pair: Pair<Rule>) -> ASTNode {
+fn build_multi_spindle_call(pair: Pair<Rule>) -> Result<ASTNode> {
     // Extract multiplier from source string (e.g., "blur<3>(...)" -> "3")
+    let span = span_of(&pair);
     let source = pair.as_str();
     let mult_start = source.find('<').unwrap() + 1;
     let mult_end = source.find('>').unwrap();
@@ -181,33 +406,45 @@ fn build_multi_spindle_call(pair: Pair<Rule>) -> ASTNode {
 
     let mut inner = pair.into_inner();
 
-    let func_name = inner.next().unwrap().as_str().to_string();
+    let func_name = symbol::intern(inner.next().unwrap().as_str());
     let args_list = inner.next().unwrap();
     let name = inner.next().unwrap().as_str().to_string();
     let outputs = build_output_spec(inner.next().unwrap());
 
-    let func_var = Box::new(ASTNode::Var(VarExpr { name: func_name }));
+    let func_var = Box::new(ASTNode::Var(VarExpr {
+        name: func_name,
+        span: span.clone(),
+    }));
 
     let mut args_slots: Vec<Vec<ASTNode>> = Vec::new();
     for bundle_or_expr_pair in args_list.into_inner() {
+        let arg_span = span_of(&bundle_or_expr_pair);
         let arg_inner = bundle_or_expr_pair.into_inner().next().unwrap();
         match arg_inner.as_rule() {
             Rule::expr_list => {
-                let items = build_expr_list(arg_inner);
+                let items = build_expr_list(arg_inner)?;
                 if items.len() != multiplier {
-                    panic!(
-                        "Bundle has {} items, but multi is {}",
-                        items.len(),
-                        multiplier
-                    );
+                    return Err(WeftError::Located {
+                        span: arg_span,
+                        message: format!(
+                            "bundle has {} item(s), but the call's multiplier is {}",
+                            items.len(),
+                            multiplier
+                        ),
+                    });
                 }
                 args_slots.push(items);
             }
             Rule::expr => {
-                let single = build_expr(arg_inner);
+                let single = build_expr(arg_inner)?;
                 args_slots.push((0..multiplier).map(|_| single.clone()).collect());
             }
-            _ => unreachable!(),
+            other => {
+                return Err(WeftError::Located {
+                    span: arg_span,
+                    message: format!("unexpected multi-spindle argument rule: {:?}", other),
+                });
+            }
         }
     }
 
@@ -217,47 +454,60 @@ fn build_multi_spindle_call(pair: Pair<Rule>) -> ASTNode {
         calls.push(ASTNode::Call(CallExpr {
             name: func_var.clone(),
             args: call_args,
+            span: span.clone(),
         }));
     }
 
-    ASTNode::InstanceBinding(InstanceBindExpr {
+    Ok(ASTNode::InstanceBinding(InstanceBindExpr {
         name,
         outputs,
-        expr: Box::new(ASTNode::Tuple(TupleExpr { items: calls })),
-    })
+        expr: Box::new(ASTNode::Tuple(TupleExpr {
+            items: calls,
+            span: span.clone(),
+        })),
+        span,
+    }))
 }
 
-fn build_spindle_call(pair: Pair<Rule>) -> ASTNode {
+fn build_spindle_call(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let func_name = inner.next().unwrap().as_str().to_string();
-    let func_var = Box::new(ASTNode::Var(VarExpr { name: func_name }));
-    let args = build_expr_list(inner.next().unwrap());
+    let func_name = symbol::intern(inner.next().unwrap().as_str());
+    let func_var = Box::new(ASTNode::Var(VarExpr {
+        name: func_name,
+        span: span.clone(),
+    }));
+    let args = build_expr_list(inner.next().unwrap())?;
     let expr = Box::new(ASTNode::Call(CallExpr {
         name: func_var,
         args,
+        span: span.clone(),
     }));
 
     let name = inner.next().unwrap().as_str().to_string();
     let outputs = build_output_spec(inner.next().unwrap());
 
-    ASTNode::InstanceBinding(InstanceBindExpr {
+    Ok(ASTNode::InstanceBinding(InstanceBindExpr {
         name,
         outputs,
         expr,
-    })
+        span,
+    }))
 }
 
-fn build_direct_bind(pair: Pair<Rule>) -> ASTNode {
+fn build_direct_bind(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
     let name = inner.next().unwrap().as_str().to_string();
     let outputs = build_output_spec(inner.next().unwrap());
-    let expr = Box::new(build_expr(inner.next().unwrap()));
+    let expr = Box::new(build_expr(inner.next().unwrap())?);
 
-    ASTNode::InstanceBinding(InstanceBindExpr {
+    Ok(ASTNode::InstanceBinding(InstanceBindExpr {
         name,
         outputs,
         expr,
-    })
+        span,
+    }))
 }
 
 fn build_output_spec(pair: Pair<Rule>) -> Vec<String> {
@@ -271,106 +521,165 @@ fn build_output_spec(pair: Pair<Rule>) -> Vec<String> {
         .collect()
 }
 
-//fn build_pragma(pair: Pair<Rule>) -> ASTNode {
-//let inner = pair.into_inner();
+/// Grammar: `"#" ~ ident ~ pragma_body`. Children: `ident` (the pragma's
+/// kind) and an optional `pragma_body` whose text is split on whitespace
+/// into `args`.
+fn build_pragma(pair: Pair<Rule>, options: &mut ParseOptions) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let kind = inner.next().unwrap().as_str().to_string();
+    let args: Vec<String> = inner
+        .next()
+        .map(|body| body.as_str().split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    apply_pragma(&span, &kind, &args, options)?;
+
+    Ok(ASTNode::Pragma(PragmaExpr { kind, args, span }))
+}
+
+/// Lets a pragma override `options` for the rest of the program from this
+/// point on -- e.g. `#strict on` flips `options.strict`. Pragma kinds
+/// this doesn't recognize (e.g. `#precision f16`) are accepted and
+/// carried onto `ASTNode::Pragma` either way; runtime is still the place
+/// that validates and acts on them. The one parse-time check is strict
+/// mode itself: once `#strict on` has been seen, a later unrecognized
+/// pragma is a parse error instead of silently passing through.
+fn apply_pragma(span: &Span, kind: &str, args: &[String], options: &mut ParseOptions) -> Result<()> {
+    let flag_is_on = |args: &[String]| args.first().map(String::as_str) != Some("off");
+
+    match kind {
+        "strict" => options.strict = flag_is_on(args),
+        "allow_experimental" => options.allow_experimental = flag_is_on(args),
+        "backend" => options.default_backend = args.first().cloned(),
+        _ if options.strict => {
+            return Err(WeftError::Located {
+                span: span.clone(),
+                message: format!(
+                    "unknown pragma #{} (strict mode rejects unrecognized pragmas)",
+                    kind
+                ),
+            });
+        }
+        _ => {}
+    }
 
-// Grammar: "#" ~ ident ~ pragma_body
-// Children: ident (type), pragma_body
-// pragma_body.as_str() gives the full text
-// Note: Pragma validation happens at runtime, not parse time!
+    Ok(())
+}
 
-fn build_if_expr(pair: Pair<Rule>) -> ASTNode {
+fn build_if_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let condition = Box::new(build_expr(inner.next().unwrap()));
-    let then_expr = Box::new(build_expr(inner.next().unwrap()));
-    let else_expr = Box::new(build_expr(inner.next().unwrap()));
+    let condition = Box::new(build_expr(inner.next().unwrap())?);
+    let then_expr = Box::new(build_expr(inner.next().unwrap())?);
+    let else_expr = Box::new(build_expr(inner.next().unwrap())?);
 
-    ASTNode::If(IfExpr {
+    Ok(ASTNode::If(IfExpr {
         condition,
         then_expr,
         else_expr,
-    })
+        span,
+    }))
 }
 
-fn build_comparison_expr(pair: Pair<Rule>) -> ASTNode {
+/// `weft.pest` would need a `match_expr` rule along these lines (not
+/// present in this snapshot):
+///   match_expr = { "match" ~ expr ~ "{" ~ match_arm+ ~ "}" }
+///   match_arm = { pattern ~ "=>" ~ expr ~ ","? }
+///   pattern = { number | string | wildcard_pattern }
+///   wildcard_pattern = { "_" }
+///
+/// A `match` whose last arm isn't the `_` wildcard is rejected here.
+fn build_match_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-
-    let left = build_arith_expr(inner.next().unwrap());
-
-    if let Some(op_pair) = inner.next() {
-        let op = op_pair.as_str().to_string();
-        let right = build_arith_expr(inner.next().unwrap());
-
-        ASTNode::Binary(BinaryExpr {
-            op,
-            left: Box::new(left),
-            right: Box::new(right),
-        })
-    } else {
-        left
+    let scrutinee = Box::new(build_expr(inner.next().unwrap())?);
+    let arms = inner.map(build_match_arm).collect::<Result<Vec<_>>>()?;
+    if !matches!(arms.last(), Some(MatchArm { pattern: Pattern::Wildcard, .. })) {
+        return Err(WeftError::Located {
+            span,
+            message: "a `match` must end with a `_` wildcard arm".to_string(),
+        });
     }
+
+    Ok(ASTNode::Match(MatchExpr {
+        scrutinee,
+        arms,
+        span,
+    }))
 }
 
-fn build_arith_expr(pair: Pair<Rule>) -> ASTNode {
+fn build_match_arm(pair: Pair<Rule>) -> Result<MatchArm> {
     let mut inner = pair.into_inner();
+    let pattern = build_pattern(inner.next().unwrap())?;
+    let body = build_expr(inner.next().unwrap())?;
 
-    let mut left = build_term(inner.next().unwrap());
-
-    while let Some(op_pair) = inner.next() {
-        let op = op_pair.as_str().to_string();
-        let right = build_term(inner.next().unwrap());
+    Ok(MatchArm { pattern, body })
+}
 
-        left = ASTNode::Binary(BinaryExpr {
-            op,
-            left: Box::new(left),
-            right: Box::new(right),
-        });
+fn build_pattern(pair: Pair<Rule>) -> Result<Pattern> {
+    let span = span_of(&pair);
+    match pair.as_rule() {
+        Rule::wildcard_pattern => Ok(Pattern::Wildcard),
+        Rule::number => Ok(Pattern::Num(pair.as_str().parse::<f64>().unwrap())),
+        Rule::string => Ok(Pattern::Str(pair.as_str().to_string())),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unsupported match pattern: {:?}", other),
+        }),
     }
-
-    left
 }
 
-fn build_term(pair: Pair<Rule>) -> ASTNode {
+fn build_comparison_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
+    let first = build_arith_expr(inner.next().unwrap())?;
+    fold_left_assoc(&span, first, inner, build_arith_expr)
+}
 
-    let mut left = build_factor(inner.next().unwrap());
-
-    while let Some(op_pair) = inner.next() {
-        let op = op_pair.as_str().to_string();
-        let right = build_factor(inner.next().unwrap());
-
-        left = ASTNode::Binary(BinaryExpr {
-            op,
-            left: Box::new(left),
-            right: Box::new(right),
-        });
-    }
-    left
+fn build_arith_expr(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let first = build_term(inner.next().unwrap())?;
+    fold_left_assoc(&span, first, inner, build_term)
 }
 
-fn build_factor(pair: Pair<Rule>) -> ASTNode {
-    let inner = pair.into_inner().next().unwrap();
-    build_power(inner)
+fn build_term(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    // `factor` is a trivial `term -> factor -> power` passthrough in the
+    // grammar, so there's no separate `build_factor` left to collapse:
+    // unwrap it inline on the way to `build_power`.
+    let build_factor = |p: Pair<Rule>| build_power(p.into_inner().next().unwrap());
+    let first = build_factor(inner.next().unwrap())?;
+    fold_left_assoc(&span, first, inner, build_factor)
 }
 
-fn build_power(pair: Pair<Rule>) -> ASTNode {
+/// `^` is the one right-associative operator (`binding_power("^")` has
+/// equal left/right power), so unlike the tiers above it recurses on the
+/// right instead of folding left.
+fn build_power(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
 
-    let left = build_unary(inner.next().unwrap());
+    let left = build_unary(inner.next().unwrap())?;
     if let Some(right_pair) = inner.next() {
-        let right = build_power(right_pair);
+        let right = build_power(right_pair)?;
+        debug_assert_eq!(binding_power("^"), (5, 5));
 
-        ASTNode::Binary(BinaryExpr {
+        Ok(ASTNode::Binary(BinaryExpr {
             op: "^".to_string(),
             left: Box::new(left),
             right: Box::new(right),
-        })
+            span,
+        }))
     } else {
-        left
+        Ok(left)
     }
 }
 
-fn build_unary(pair: Pair<Rule>) -> ASTNode {
+fn build_unary(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let source = pair.as_str();
     let mut inner = pair.into_inner();
     let first = inner.next().unwrap();
@@ -382,141 +691,184 @@ fn build_unary(pair: Pair<Rule>) -> ASTNode {
             } else {
                 "NOT".to_string()
             };
-            let expr = build_unary(first);
+            let expr = build_unary(first)?;
 
-            ASTNode::Unary(UnaryExpr {
+            Ok(ASTNode::Unary(UnaryExpr {
                 op,
                 expr: Box::new(expr),
-            })
+                span,
+            }))
         }
         Rule::postfix => build_postfix(first),
-        _ => unreachable!(),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected unary rule: {:?}", other),
+        }),
     }
 }
 
-fn build_postfix(pair: Pair<Rule>) -> ASTNode {
+fn build_postfix(pair: Pair<Rule>) -> Result<ASTNode> {
     let mut inner = pair.into_inner();
-    let mut base = build_atom(inner.next().unwrap());
+    let mut base = build_atom(inner.next().unwrap())?;
 
     for postfix_op_pair in inner {
-        base = build_postfix_op(base, postfix_op_pair);
+        base = build_postfix_op(base, postfix_op_pair)?;
     }
 
-    base
+    Ok(base)
 }
 
-fn build_postfix_op(base: ASTNode, pair: Pair<Rule>) -> ASTNode {
+fn build_postfix_op(base: ASTNode, pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
     let first = inner.next().unwrap();
 
     match first.as_rule() {
         Rule::ident => {
-            let strand_name = first.as_str().to_string();
+            let strand_name = symbol::intern(first.as_str());
             if let Some(axis_mapping_list_pair) = inner.next() {
-                let mappings = build_axis_mapping_list(axis_mapping_list_pair);
-                ASTNode::StrandRemap(StrandRemapExpr {
+                let mappings = build_axis_mapping_list(axis_mapping_list_pair)?;
+                Ok(ASTNode::StrandRemap(StrandRemapExpr {
                     base: Box::new(base),
                     strand: strand_name,
                     mappings,
-                })
+                    span,
+                }))
             } else {
-                ASTNode::StrandAccess(StrandAccessExpr {
+                Ok(ASTNode::StrandAccess(StrandAccessExpr {
                     base: Box::new(base),
-                    out: Box::new(ASTNode::Var(VarExpr { name: strand_name })),
-                })
+                    out: Box::new(ASTNode::Var(VarExpr {
+                        name: strand_name,
+                        span: span.clone(),
+                    })),
+                    // The grammar has no delay-marker token yet, so a
+                    // parsed strand access is never a feedback read.
+                    delayed: false,
+                    span,
+                }))
             }
         }
         Rule::expr_list => {
-            let args = build_expr_list(first);
-            ASTNode::Call(CallExpr {
+            let args = build_expr_list(first)?;
+            Ok(ASTNode::Call(CallExpr {
                 name: Box::new(base),
                 args,
-            })
+                span,
+            }))
         }
-        Rule::expr => ASTNode::Index(IndexExpr {
+        Rule::expr => Ok(ASTNode::Index(IndexExpr {
             base: Box::new(base),
-            index: Box::new(build_expr(first)),
+            index: Box::new(build_expr(first)?),
+            span,
+        })),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unexpected postfix_op rule: {:?}", other),
         }),
-        _ => unreachable!("Unexpected postfix_op rule: {:?}", first.as_rule()),
     }
 }
 
-fn build_atom(pair: Pair<Rule>) -> ASTNode {
+fn build_atom(pair: Pair<Rule>) -> Result<ASTNode> {
+    let span = span_of(&pair);
     let source = pair.as_str();
     let mut inner = pair.into_inner();
     let first = inner.next().unwrap();
 
     if source.starts_with("me") && source.contains('@') {
-        let field = first.as_str().to_string();
-        return ASTNode::Me(MeExpr { field });
+        let field = symbol::intern(first.as_str());
+        return Ok(ASTNode::Me(MeExpr { field, span }));
     }
 
     match first.as_rule() {
         Rule::ident => {
-            let ident = first.as_str().to_string();
-            ASTNode::Var(VarExpr { name: ident })
+            let ident = symbol::intern(first.as_str());
+            Ok(ASTNode::Var(VarExpr { name: ident, span }))
         }
         Rule::number => {
-            let value = first.as_str().parse::<f64>().unwrap();
-            ASTNode::Num(NumExpr { v: value })
+            let text = first.as_str();
+            let kind = if text.contains('.') || text.contains('e') || text.contains('E') {
+                NumKind::Float(text.parse::<f64>().unwrap())
+            } else {
+                // An integer literal too large for i64 (e.g. 20+ digits) falls
+                // back to Float instead of panicking -- same as it did before
+                // NumKind::Int existed, just with an explicit fallback instead
+                // of every literal losing precision unconditionally.
+                match text.parse::<i64>() {
+                    Ok(i) => NumKind::Int(i),
+                    Err(_) => NumKind::Float(text.parse::<f64>().unwrap()),
+                }
+            };
+            let value = match kind {
+                NumKind::Int(i) => i as f64,
+                NumKind::Float(f) => f,
+            };
+            Ok(ASTNode::Num(NumExpr { v: value, kind, span }))
         }
-        Rule::string => ASTNode::Str(StrExpr {
+        Rule::string => Ok(ASTNode::Str(StrExpr {
             v: first.as_str().to_string(),
-        }),
+            span,
+        })),
         Rule::expr => build_expr(first),
         Rule::expr_list => {
-            let items = build_expr_list(first);
-            ASTNode::Tuple(TupleExpr { items })
+            let items = build_expr_list(first)?;
+            Ok(ASTNode::Tuple(TupleExpr { items, span }))
         }
-        _ => todo!("Implement other atom expressions: {:?}", first.as_rule()),
+        other => Err(WeftError::Located {
+            span,
+            message: format!("unsupported atom expression: {:?}", other),
+        }),
     }
 }
 
-fn build_ident_list(pair: Pair<Rule>) -> Vec<String> {
+fn build_ident_list(pair: Pair<Rule>) -> Vec<Symbol> {
     pair.into_inner()
-        .map(|ident_pair| ident_pair.as_str().to_string())
+        .map(|ident_pair| symbol::intern(ident_pair.as_str()))
         .collect()
 }
 
-fn build_expr_list(pair: Pair<Rule>) -> Vec<ASTNode> {
-    pair.into_inner()
-        .map(|expr_pair| build_expr(expr_pair))
-        .collect()
+fn build_expr_list(pair: Pair<Rule>) -> Result<Vec<ASTNode>> {
+    pair.into_inner().map(build_expr).collect()
 }
 
-fn build_axis_mapping_list(pair: Pair<Rule>) -> Vec<AxisMapping> {
-    pair.into_inner()
-        .map(|mapping_pair| build_axis_mapping(mapping_pair))
-        .collect()
+fn build_axis_mapping_list(pair: Pair<Rule>) -> Result<Vec<AxisMapping>> {
+    pair.into_inner().map(build_axis_mapping).collect()
 }
 
-fn build_axis_mapping(pair: Pair<Rule>) -> AxisMapping {
+fn build_axis_mapping(pair: Pair<Rule>) -> Result<AxisMapping> {
     let mut inner = pair.into_inner();
     let axis_ref = build_axis_ref(inner.next().unwrap());
-    let value_expr = build_expr(inner.next().unwrap());
+    let value_expr = build_expr(inner.next().unwrap())?;
 
-    AxisMapping {
+    Ok(AxisMapping {
         axis: Box::new(axis_ref),
         expr: Box::new(value_expr),
-    }
+    })
 }
 
 fn build_axis_ref(pair: Pair<Rule>) -> ASTNode {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
-    let instance_name = inner.next().unwrap().as_str().to_string();
-    let output_name = inner.next().unwrap().as_str().to_string();
+    let instance_name = symbol::intern(inner.next().unwrap().as_str());
+    let output_name = symbol::intern(inner.next().unwrap().as_str());
 
     ASTNode::StrandAccess(StrandAccessExpr {
         base: Box::new(ASTNode::Var(VarExpr {
             name: instance_name,
+            span: span.clone(),
         })),
-        out: Box::new(ASTNode::Var(VarExpr { name: output_name })),
+        out: Box::new(ASTNode::Var(VarExpr {
+            name: output_name,
+            span: span.clone(),
+        })),
+        delayed: false,
+        span,
     })
 }
 
-fn build_output_statement(pair: Pair<Rule>) -> ASTNode {
+fn build_output_statement(pair: Pair<Rule>) -> Result<ASTNode> {
     use std::collections::HashMap;
 
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
     let context = inner.next().unwrap().as_str().to_string();
     let stmt_arg_list = inner.next().unwrap();
@@ -526,33 +878,36 @@ fn build_output_statement(pair: Pair<Rule>) -> ASTNode {
     let mut positional_args = Vec::new();
 
     for stmt_arg_pair in stmt_arg_list.into_inner() {
+        let arg_span = span_of(&stmt_arg_pair);
         let inner = stmt_arg_pair.into_inner();
         let children: Vec<_> = inner.collect();
 
         if children.len() == 2 {
             let name = children[0].as_str().to_string();
-            let value = build_expr(children[1].clone());
+            let value = build_expr(children[1].clone())?;
 
             let named_arg = ASTNode::NamedArg(NamedArg {
                 name: name.clone(),
                 value: Box::new(value.clone()),
+                span: arg_span,
             });
 
             args.push(named_arg);
             named_args.insert(name, value);
         } else {
-            let expr = build_expr(children[0].clone());
+            let expr = build_expr(children[0].clone())?;
             args.push(expr.clone());
             positional_args.push(expr);
         }
     }
 
-    ASTNode::Backend(BackendExpr {
+    Ok(ASTNode::Backend(BackendExpr {
         context,
         args,
         named_args,
         positional_args,
-    })
+        span,
+    }))
 }
 
 #[cfg(test)]
@@ -573,6 +928,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_integer_literal_overflowing_i64_falls_back_to_float() {
+        let result = parse("x<a> = 123456789012345678901234567890").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Num(num) => assert!(matches!(num.kind, NumKind::Float(_))),
+                _ => panic!("Expected Num"),
+            },
+            _ => panic!("Expected InstanceBinding"),
+        }
+    }
+
     #[test]
     fn test_string_literal() {
         let result = parse("x<a> = \"hello\"").unwrap();
@@ -680,6 +1048,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_match_expression() {
+        let result = parse("x<a> = match n { 1 => 10, \"two\" => 20, _ => 30 }").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Match(match_expr) => {
+                    match match_expr.scrutinee.as_ref() {
+                        ASTNode::Var(v) => assert_eq!(v.name, "n"),
+                        _ => panic!("Expected Var scrutinee"),
+                    }
+                    assert_eq!(match_expr.arms.len(), 3);
+                    match &match_expr.arms[0].pattern {
+                        Pattern::Num(n) => assert_eq!(*n, 1.0),
+                        _ => panic!("Expected Num pattern"),
+                    }
+                    match &match_expr.arms[1].pattern {
+                        Pattern::Str(_) => {}
+                        _ => panic!("Expected Str pattern"),
+                    }
+                    match &match_expr.arms[2].pattern {
+                        Pattern::Wildcard => {}
+                        _ => panic!("Expected Wildcard pattern"),
+                    }
+                }
+                _ => panic!("Expected Match"),
+            },
+            _ => panic!("Expected InstanceBinding"),
+        }
+    }
+
     #[test]
     fn test_unary() {
         let result = parse("x<a> = -5").unwrap();
@@ -835,14 +1234,48 @@ mod tests {
                 ASTNode::Block(block) => match &block.body[0] {
                     ASTNode::ForLoop(for_loop) => {
                         assert_eq!(for_loop.var, "i");
-                        match for_loop.start.as_ref() {
-                            ASTNode::Num(n) => assert_eq!(n.v, 0.0),
-                            _ => panic!("Expected Num"),
+                        match &for_loop.kind {
+                            ForKind::Range { start, end, step } => {
+                                match start.as_ref() {
+                                    ASTNode::Num(n) => assert_eq!(n.v, 0.0),
+                                    _ => panic!("Expected Num"),
+                                }
+                                match end.as_ref() {
+                                    ASTNode::Num(n) => assert_eq!(n.v, 10.0),
+                                    _ => panic!("Expected Num"),
+                                }
+                                assert!(step.is_none());
+                            }
+                            other => panic!("Expected ForKind::Range, got {:?}", other),
                         }
-                        match for_loop.end.as_ref() {
-                            ASTNode::Num(n) => assert_eq!(n.v, 10.0),
-                            _ => panic!("Expected Num"),
+                    }
+                    _ => panic!("Expected ForLoop"),
+                },
+                _ => panic!("Expected Block"),
+            },
+            _ => panic!("Expected SpindleDef"),
+        }
+    }
+
+    #[test]
+    fn test_for_loop_step_and_else() {
+        let result = parse(
+            "spindle test() :: <x> { for i in (10 to 0 step -1) { out x = i } else { out x = -1 } }",
+        )
+        .unwrap();
+
+        match &result.statements[0] {
+            ASTNode::SpindleDef(def) => match def.body.as_ref() {
+                ASTNode::Block(block) => match &block.body[0] {
+                    ASTNode::ForLoop(for_loop) => {
+                        match &for_loop.kind {
+                            ForKind::Range { step, .. } => match step.as_deref() {
+                                Some(ASTNode::Unary(u)) => assert_eq!(u.op, "-"),
+                                other => panic!("Expected negative step, got {:?}", other),
+                            },
+                            other => panic!("Expected ForKind::Range, got {:?}", other),
                         }
+                        assert!(for_loop.else_body.is_some());
                     }
                     _ => panic!("Expected ForLoop"),
                 },
@@ -852,6 +1285,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_for_loop_over_collection() {
+        let result = parse("spindle test() :: <x> { for px in image@rgb { out x = px } }").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::SpindleDef(def) => match def.body.as_ref() {
+                ASTNode::Block(block) => match &block.body[0] {
+                    ASTNode::ForLoop(for_loop) => {
+                        assert_eq!(for_loop.var, "px");
+                        match &for_loop.kind {
+                            ForKind::Each { iterable } => {
+                                assert!(matches!(iterable.as_ref(), ASTNode::StrandAccess(_)));
+                            }
+                            other => panic!("Expected ForKind::Each, got {:?}", other),
+                        }
+                    }
+                    _ => panic!("Expected ForLoop"),
+                },
+                _ => panic!("Expected Block"),
+            },
+            _ => panic!("Expected SpindleDef"),
+        }
+    }
+
+    #[test]
+    fn test_for_loop_step_direction_mismatch_is_an_error() {
+        let result = parse("spindle test() :: <x> { for i in (0 to 10 step -1) { out x = i } }");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_return_statement() {
+        let result = parse("spindle test() :: <x> { return 1 }").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::SpindleDef(def) => match def.body.as_ref() {
+                ASTNode::Block(block) => match &block.body[0] {
+                    ASTNode::Return(ret) => {
+                        assert!(matches!(ret.expr.as_deref(), Some(ASTNode::Num(n)) if n.v == 1.0));
+                    }
+                    _ => panic!("Expected Return"),
+                },
+                _ => panic!("Expected Block"),
+            },
+            _ => panic!("Expected SpindleDef"),
+        }
+    }
+
+    #[test]
+    fn test_bare_return_statement() {
+        let result = parse("spindle test() :: <x> { return }").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::SpindleDef(def) => match def.body.as_ref() {
+                ASTNode::Block(block) => match &block.body[0] {
+                    ASTNode::Return(ret) => assert!(ret.expr.is_none()),
+                    _ => panic!("Expected Return"),
+                },
+                _ => panic!("Expected Block"),
+            },
+            _ => panic!("Expected SpindleDef"),
+        }
+    }
+
     #[test]
     fn test_play_stmt() {
         let result = parse("play(audio)").unwrap();
@@ -1118,4 +1616,113 @@ mod tests {
             _ => panic!("Expected InstanceBinding"),
         }
     }
+
+    #[test]
+    fn test_pipe_stage() {
+        let result = parse("x<a> = src |> blur(radius)").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Call(call) => {
+                    match call.name.as_ref() {
+                        ASTNode::Var(v) => assert_eq!(v.name, "blur"),
+                        _ => panic!("Expected Var"),
+                    }
+                    assert_eq!(call.args.len(), 2);
+                    match &call.args[0] {
+                        ASTNode::Var(v) => assert_eq!(v.name, "src"),
+                        _ => panic!("Expected Var src as first (piped-in) argument"),
+                    }
+                    match &call.args[1] {
+                        ASTNode::Var(v) => assert_eq!(v.name, "radius"),
+                        _ => panic!("Expected Var radius"),
+                    }
+                }
+                _ => panic!("Expected Call"),
+            },
+            _ => panic!("Expected InstanceBinding"),
+        }
+    }
+
+    #[test]
+    fn test_pipe_chain() {
+        let result = parse("x<a> = src |> blur |> sharpen(k)").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Call(outer) => {
+                    match outer.name.as_ref() {
+                        ASTNode::Var(v) => assert_eq!(v.name, "sharpen"),
+                        _ => panic!("Expected Var"),
+                    }
+                    assert_eq!(outer.args.len(), 2);
+                    match &outer.args[0] {
+                        ASTNode::Call(inner) => {
+                            match inner.name.as_ref() {
+                                ASTNode::Var(v) => assert_eq!(v.name, "blur"),
+                                _ => panic!("Expected Var"),
+                            }
+                            assert_eq!(inner.args.len(), 1);
+                            match &inner.args[0] {
+                                ASTNode::Var(v) => assert_eq!(v.name, "src"),
+                                _ => panic!("Expected Var src"),
+                            }
+                        }
+                        _ => panic!("Expected inner Call from the first pipe stage"),
+                    }
+                    match &outer.args[1] {
+                        ASTNode::Var(v) => assert_eq!(v.name, "k"),
+                        _ => panic!("Expected Var k"),
+                    }
+                }
+                _ => panic!("Expected Call"),
+            },
+            _ => panic!("Expected InstanceBinding"),
+        }
+    }
+
+    #[test]
+    fn test_pipe_stage_binds_looser_than_arithmetic() {
+        let result = parse("x<a> = a + b |> f").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::InstanceBinding(bind) => match bind.expr.as_ref() {
+                ASTNode::Call(call) => {
+                    match call.name.as_ref() {
+                        ASTNode::Var(v) => assert_eq!(v.name, "f"),
+                        _ => panic!("Expected Var"),
+                    }
+                    assert_eq!(call.args.len(), 1);
+                    match &call.args[0] {
+                        ASTNode::Binary(b) => assert_eq!(b.op, "+"),
+                        other => panic!("Expected (a + b) piped into f, got {:?}", other),
+                    }
+                }
+                _ => panic!("Expected Call"),
+            },
+            _ => panic!("Expected InstanceBinding"),
+        }
+    }
+
+    #[test]
+    fn test_pragma() {
+        let result = parse("#backend gpu\nx<a> = 1").unwrap();
+
+        match &result.statements[0] {
+            ASTNode::Pragma(pragma) => {
+                assert_eq!(pragma.kind, "backend");
+                assert_eq!(pragma.args, vec!["gpu".to_string()]);
+            }
+            _ => panic!("Expected Pragma"),
+        }
+    }
+
+    #[test]
+    fn test_strict_pragma_rejects_unknown_pragma() {
+        let ok = parse("#strict off\n#precision f16\nx<a> = 1");
+        assert!(ok.is_ok());
+
+        let err = parse("#strict on\n#precision f16\nx<a> = 1");
+        assert!(err.is_err());
+    }
 }
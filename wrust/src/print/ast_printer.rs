@@ -0,0 +1,448 @@
+//! Lowers a Weft [`Program`] into the [`Token`] stream consumed by the
+//! Oppen printer ([`super::engine`]), producing canonical, idempotent
+//! source text.
+
+use super::engine::{self, BreakType, Token};
+use crate::ast::*;
+
+const MARGIN: isize = 80;
+
+/// Format a whole program as canonical Weft source.
+pub fn format_program(program: &Program) -> String {
+    let mut tokens = Vec::new();
+    for (i, stmt) in program.statements.iter().enumerate() {
+        if i > 0 {
+            tokens.push(Token::Break { blank: 0, indent: 0 });
+        }
+        lower_statement(stmt, &mut tokens);
+    }
+    engine::print(tokens, MARGIN)
+}
+
+fn begin(tokens: &mut Vec<Token>, indent: isize, breaktype: BreakType) {
+    tokens.push(Token::Begin { indent, breaktype });
+}
+
+fn end(tokens: &mut Vec<Token>) {
+    tokens.push(Token::End);
+}
+
+fn text(tokens: &mut Vec<Token>, s: impl Into<String>) {
+    tokens.push(Token::Text(s.into()));
+}
+
+fn soft_break(tokens: &mut Vec<Token>) {
+    tokens.push(Token::Break { blank: 1, indent: 0 });
+}
+
+fn lower_statement(node: &ASTNode, tokens: &mut Vec<Token>) {
+    match node {
+        ASTNode::SpindleDef(def) => lower_spindle_def(def, tokens),
+        ASTNode::Assignment(assign) if !assign.is_output => {
+            text(tokens, format!("me<{}> {} ", assign.name, assign.op));
+            lower_expr(&assign.expr, tokens);
+        }
+        ASTNode::InstanceBinding(bind) => lower_instance_binding(bind, tokens),
+        ASTNode::Backend(backend) => lower_backend(backend, tokens),
+        ASTNode::Pragma(pragma) => lower_pragma(pragma, tokens),
+        other => lower_expr(other, tokens),
+    }
+}
+
+fn lower_pragma(pragma: &PragmaExpr, tokens: &mut Vec<Token>) {
+    text(tokens, format!("#{}", pragma.kind));
+    if !pragma.args.is_empty() {
+        text(tokens, format!(" {}", pragma.args.join(" ")));
+    }
+}
+
+fn lower_spindle_def(def: &SpindleDef, tokens: &mut Vec<Token>) {
+    let inputs: Vec<&str> = def.inputs.iter().map(|s| s.resolve()).collect();
+    let outputs: Vec<&str> = def.outputs.iter().map(|s| s.resolve()).collect();
+    text(
+        tokens,
+        format!(
+            "spindle {}({}) :: <{}> {{",
+            def.name,
+            inputs.join(", "),
+            outputs.join(", ")
+        ),
+    );
+    begin(tokens, 2, BreakType::Consistent);
+    lower_block_body(&def.body, tokens);
+    end(tokens);
+    text(tokens, "}");
+}
+
+fn lower_block_body(body: &ASTNode, tokens: &mut Vec<Token>) {
+    if let ASTNode::Block(block) = body {
+        for stmt in &block.body {
+            tokens.push(Token::Break { blank: 0, indent: 2 });
+            lower_block_statement(stmt, tokens);
+        }
+        tokens.push(Token::Break { blank: 0, indent: 0 });
+    }
+}
+
+fn lower_block_statement(node: &ASTNode, tokens: &mut Vec<Token>) {
+    match node {
+        ASTNode::Assignment(assign) if assign.is_output => {
+            text(tokens, format!("out {} = ", assign.name));
+            lower_expr(&assign.expr, tokens);
+        }
+        ASTNode::Assignment(assign) => {
+            text(tokens, format!("{} {} ", assign.name, assign.op));
+            lower_expr(&assign.expr, tokens);
+        }
+        ASTNode::ForLoop(for_loop) => {
+            text(tokens, format!("for {} in ", for_loop.var));
+            match &for_loop.kind {
+                ForKind::Range { start, end, step } => {
+                    text(tokens, "(");
+                    lower_expr(start, tokens);
+                    text(tokens, " to ");
+                    lower_expr(end, tokens);
+                    if let Some(step) = step {
+                        text(tokens, " step ");
+                        lower_expr(step, tokens);
+                    }
+                    text(tokens, ")");
+                }
+                ForKind::Each { iterable } => lower_expr(iterable, tokens),
+            }
+            text(tokens, " {");
+            begin(tokens, 2, BreakType::Consistent);
+            lower_block_body(&for_loop.body, tokens);
+            end(tokens);
+            text(tokens, "}");
+            if let Some(else_body) = &for_loop.else_body {
+                text(tokens, " else {");
+                begin(tokens, 2, BreakType::Consistent);
+                lower_block_body(else_body, tokens);
+                end(tokens);
+                text(tokens, "}");
+            }
+        }
+        ASTNode::Return(ret) => {
+            text(tokens, "return");
+            if let Some(expr) = &ret.expr {
+                text(tokens, " ");
+                lower_expr(expr, tokens);
+            }
+        }
+        other => lower_expr(other, tokens),
+    }
+}
+
+fn lower_instance_binding(bind: &InstanceBindExpr, tokens: &mut Vec<Token>) {
+    begin(tokens, 2, BreakType::Inconsistent);
+    lower_expr(&bind.expr, tokens);
+    text(tokens, format!(" :: {}<{}>", bind.name, bind.outputs.join(", ")));
+    end(tokens);
+}
+
+fn lower_backend(backend: &BackendExpr, tokens: &mut Vec<Token>) {
+    text(tokens, format!("{}(", backend.context));
+    begin(tokens, 2, BreakType::Inconsistent);
+    for (i, arg) in backend.positional_args.iter().enumerate() {
+        if i > 0 {
+            text(tokens, ",");
+            soft_break(tokens);
+        }
+        lower_expr(arg, tokens);
+    }
+    let mut names: Vec<&String> = backend.named_args.keys().collect();
+    names.sort();
+    for name in names {
+        if !backend.positional_args.is_empty() {
+            text(tokens, ",");
+            soft_break(tokens);
+        }
+        text(tokens, format!("{}: ", name));
+        lower_expr(&backend.named_args[name], tokens);
+    }
+    end(tokens);
+    text(tokens, ")");
+}
+
+/// Prints `bin`, parenthesizing either operand whenever printing it bare
+/// would re-parse into a different tree than the one being printed --
+/// i.e. whenever the operand is itself a `Binary` whose precedence
+/// (`parser::binding_power`, the same tier table the parser climbs)
+/// doesn't already guarantee it binds together on that side. `^` is the
+/// one right-associative operator (equal left/right power), so it's the
+/// only case where an equal-precedence child needs parens on the left
+/// rather than the right.
+fn lower_binary(bin: &BinaryExpr, tokens: &mut Vec<Token>) {
+    let parent_level = crate::parser::binding_power(&bin.op).0;
+    let right_assoc = bin.op == "^";
+
+    begin(tokens, 2, BreakType::Inconsistent);
+    lower_operand(&bin.left, tokens, parent_level, right_assoc);
+    soft_break(tokens);
+    text(tokens, format!("{} ", bin.op));
+    lower_operand(&bin.right, tokens, parent_level, !right_assoc);
+    end(tokens);
+}
+
+/// Prints one operand of a `Binary`, wrapping it in parens when it's a
+/// `Binary` whose precedence is lower than `parent_level`, or equal and
+/// `needs_parens_at_equal_level` (true for the side where the parent's
+/// own associativity wouldn't otherwise regroup it correctly: the right
+/// side of a left-associative operator, or the left side of `^`).
+fn lower_operand(
+    node: &ASTNode,
+    tokens: &mut Vec<Token>,
+    parent_level: u8,
+    needs_parens_at_equal_level: bool,
+) {
+    let needs_parens = match node {
+        ASTNode::Binary(inner) => {
+            let level = crate::parser::binding_power(&inner.op).0;
+            level < parent_level || (level == parent_level && needs_parens_at_equal_level)
+        }
+        _ => false,
+    };
+
+    if needs_parens {
+        text(tokens, "(");
+        lower_expr(node, tokens);
+        text(tokens, ")");
+    } else {
+        lower_expr(node, tokens);
+    }
+}
+
+fn lower_expr(node: &ASTNode, tokens: &mut Vec<Token>) {
+    match node {
+        ASTNode::Num(n) => text(tokens, format_num(n.v)),
+        ASTNode::Str(s) => text(tokens, s.v.clone()),
+        ASTNode::Var(v) => text(tokens, v.name.resolve()),
+        ASTNode::Me(me) => text(tokens, format!("me@{}", me.field)),
+        ASTNode::Unary(un) => {
+            text(tokens, format!("{} ", un.op));
+            lower_expr(&un.expr, tokens);
+        }
+        ASTNode::Binary(bin) => lower_binary(bin, tokens),
+        ASTNode::Call(call) => {
+            lower_expr(&call.name, tokens);
+            text(tokens, "(");
+            begin(tokens, 2, BreakType::Inconsistent);
+            for (i, arg) in call.args.iter().enumerate() {
+                if i > 0 {
+                    text(tokens, ",");
+                    soft_break(tokens);
+                }
+                lower_expr(arg, tokens);
+            }
+            end(tokens);
+            text(tokens, ")");
+        }
+        ASTNode::Tuple(tuple) => {
+            text(tokens, "<");
+            begin(tokens, 2, BreakType::Inconsistent);
+            for (i, item) in tuple.items.iter().enumerate() {
+                if i > 0 {
+                    text(tokens, ",");
+                    soft_break(tokens);
+                }
+                lower_expr(item, tokens);
+            }
+            end(tokens);
+            text(tokens, ">");
+        }
+        ASTNode::Index(index) => {
+            lower_expr(&index.base, tokens);
+            text(tokens, "[");
+            lower_expr(&index.index, tokens);
+            text(tokens, "]");
+        }
+        ASTNode::StrandAccess(access) => {
+            lower_expr(&access.base, tokens);
+            text(tokens, "@");
+            lower_expr(&access.out, tokens);
+        }
+        ASTNode::StrandRemap(remap) => {
+            lower_expr(&remap.base, tokens);
+            text(tokens, format!("@{}(", remap.strand));
+            begin(tokens, 2, BreakType::Inconsistent);
+            for (i, mapping) in remap.mappings.iter().enumerate() {
+                if i > 0 {
+                    text(tokens, ",");
+                    soft_break(tokens);
+                }
+                lower_expr(&mapping.axis, tokens);
+                text(tokens, " ~ ");
+                lower_expr(&mapping.expr, tokens);
+            }
+            end(tokens);
+            text(tokens, ")");
+        }
+        ASTNode::If(if_expr) => {
+            begin(tokens, 2, BreakType::Consistent);
+            text(tokens, "if ");
+            lower_expr(&if_expr.condition, tokens);
+            soft_break(tokens);
+            text(tokens, "then ");
+            lower_expr(&if_expr.then_expr, tokens);
+            soft_break(tokens);
+            text(tokens, "else ");
+            lower_expr(&if_expr.else_expr, tokens);
+            end(tokens);
+        }
+        ASTNode::Match(match_expr) => {
+            text(tokens, "match ");
+            lower_expr(&match_expr.scrutinee, tokens);
+            text(tokens, " {");
+            begin(tokens, 2, BreakType::Consistent);
+            for arm in &match_expr.arms {
+                soft_break(tokens);
+                lower_pattern(&arm.pattern, tokens);
+                text(tokens, " => ");
+                lower_expr(&arm.body, tokens);
+                text(tokens, ",");
+            }
+            end(tokens);
+            soft_break(tokens);
+            text(tokens, "}");
+        }
+        ASTNode::NamedArg(arg) => {
+            text(tokens, format!("{}: ", arg.name));
+            lower_expr(&arg.value, tokens);
+        }
+        ASTNode::Block(block) => {
+            text(tokens, "{");
+            begin(tokens, 2, BreakType::Consistent);
+            lower_block_body(&ASTNode::Block(block.clone()), tokens);
+            end(tokens);
+            text(tokens, "}");
+        }
+        ASTNode::Assignment(_) | ASTNode::Backend(_) | ASTNode::SpindleDef(_)
+        | ASTNode::InstanceBinding(_) | ASTNode::ForLoop(_) | ASTNode::Return(_)
+        | ASTNode::Pragma(_) | ASTNode::Program(_) => {
+            lower_statement(node, tokens);
+        }
+    }
+}
+
+fn lower_pattern(pattern: &Pattern, tokens: &mut Vec<Token>) {
+    match pattern {
+        Pattern::Num(n) => text(tokens, format_num(*n)),
+        Pattern::Str(s) => text(tokens, s.clone()),
+        Pattern::Wildcard => text(tokens, "_"),
+    }
+}
+
+fn format_num(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{}", v as i64)
+    } else {
+        format!("{}", v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn roundtrip_is_stable(src: &str) {
+        let ast = parse(src).unwrap();
+        let once = format_program(&ast);
+        let reparsed = parse(&once).unwrap();
+        let twice = format_program(&reparsed);
+        assert_eq!(once, twice, "formatting {:?} was not idempotent", src);
+    }
+
+    /// The tree's JSON form with every `span` field removed, so two parses
+    /// compare equal when their structure and values match, wherever in
+    /// the source text each node sat.
+    fn tree_shape(program: &Program) -> serde_json::Value {
+        fn strip_spans(value: &mut serde_json::Value) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    map.remove("span");
+                    map.values_mut().for_each(strip_spans);
+                }
+                serde_json::Value::Array(items) => items.iter_mut().for_each(strip_spans),
+                _ => {}
+            }
+        }
+        let mut value = serde_json::to_value(program).unwrap();
+        strip_spans(&mut value);
+        value
+    }
+
+    #[test]
+    fn formats_simple_binding() {
+        roundtrip_is_stable("x<a> = 1 + 2");
+    }
+
+    #[test]
+    fn formats_spindle_def() {
+        roundtrip_is_stable("spindle add(a, b) :: <sum> { out sum = a + b }");
+    }
+
+    #[test]
+    fn formats_backend_call() {
+        roundtrip_is_stable("render(x, workers: 4)");
+    }
+
+    #[test]
+    fn formats_nested_expr() {
+        roundtrip_is_stable("x<a> = (1 + 2) * (3 + 4)");
+    }
+
+    /// Idempotence (`roundtrip_is_stable`) only proves `format(format(x))
+    /// == format(x)`; it can't catch a formatter that drops meaning-
+    /// changing parens on its first pass, since the second pass is just
+    /// as wrong in the same way. This asserts the formatted text actually
+    /// re-parses to the same tree as the source, which a precedence-
+    /// unaware printer for `(1 + 2) * (3 + 4)` fails (it would print the
+    /// flat `1 + 2 * 3 + 4`, which re-parses as `(1 + (2 * 3)) + 4`).
+    #[test]
+    fn formatted_nested_expr_reparses_to_the_same_ast() {
+        let src = "x<a> = (1 + 2) * (3 + 4)";
+        let ast = parse(src).unwrap();
+        let formatted = format_program(&ast);
+        let reparsed = parse(&formatted).unwrap();
+
+        assert_eq!(
+            tree_shape(&ast), tree_shape(&reparsed),
+            "formatting {:?} as {:?} changed its AST on reparse",
+            src, formatted
+        );
+    }
+
+    #[test]
+    fn formats_right_associative_power_without_unnecessary_parens() {
+        roundtrip_is_stable("x<a> = 2 ^ 3 ^ 4");
+    }
+
+    #[test]
+    fn formats_left_associated_power_keeps_grouping_parens() {
+        let src = "x<a> = (2 ^ 3) ^ 4";
+        let ast = parse(src).unwrap();
+        let formatted = format_program(&ast);
+        let reparsed = parse(&formatted).unwrap();
+
+        assert_eq!(tree_shape(&ast), tree_shape(&reparsed));
+    }
+
+    #[test]
+    fn formats_match_expr() {
+        roundtrip_is_stable("x<a> = match n { 1 => 10, _ => 20 }");
+    }
+
+    #[test]
+    fn formats_pragma() {
+        roundtrip_is_stable("#backend gpu");
+    }
+
+    #[test]
+    fn formats_for_loop_with_step_and_else() {
+        roundtrip_is_stable(
+            "spindle test() :: <x> { for i in (10 to 0 step -1) { out x = i } else { out x = -1 } }",
+        );
+    }
+}
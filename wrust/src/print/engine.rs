@@ -0,0 +1,357 @@
+//! A line-breaking pretty-printer based on Derek Oppen's 1980 algorithm
+//! ("Pretty Printing", ACM TOPLAS 2(4)).
+//!
+//! Callers push a stream of [`Token`]s describing the *logical* structure of
+//! the output (groups that may or may not fit on one line, and the breaks
+//! inside them) and the printer decides, with bounded lookahead, which
+//! breaks become newlines. The scan stack never needs to buffer more than
+//! `margin` tokens, so printing a document of any size runs in O(n) time
+//! and O(margin) space.
+
+use std::collections::VecDeque;
+
+/// How the breaks inside a group are allowed to render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakType {
+    /// If the group doesn't fit, every break in it becomes a newline.
+    Consistent,
+    /// If the group doesn't fit, only the breaks that are individually
+    /// needed become newlines; others still print as spaces.
+    Inconsistent,
+}
+
+#[derive(Debug, Clone)]
+pub enum Token {
+    /// An atomic piece of text with a known display width.
+    Text(String),
+    /// A potential line break: `blank` spaces if it prints flat, otherwise
+    /// a newline followed by `indent` columns of leading whitespace.
+    Break { blank: usize, indent: isize },
+    /// Opens a group at `indent` columns of additional indentation.
+    Begin { indent: isize, breaktype: BreakType },
+    /// Closes the most recently opened group.
+    End,
+}
+
+impl Token {
+    fn flat_width(&self) -> isize {
+        match self {
+            Token::Text(s) => s.chars().count() as isize,
+            Token::Break { blank, .. } => *blank as isize,
+            Token::Begin { .. } | Token::End => 0,
+        }
+    }
+}
+
+/// One entry in the buffer of tokens awaiting a size decision.
+struct BufEntry {
+    token: Token,
+    size: isize,
+}
+
+/// Printer state. Construct with [`Printer::new`], feed it tokens with
+/// [`Printer::push`], then call [`Printer::finish`] to retrieve the output.
+pub struct Printer {
+    margin: isize,
+    space: isize,
+    out: String,
+
+    // Ring buffer of tokens whose size is not yet resolved, paired with the
+    // running total used to recover each entry's contribution.
+    buf: VecDeque<BufEntry>,
+    // Stack of ids (from `next_id`, a monotonically increasing logical
+    // stream) of buffered Begin/Break/End entries; their sizes live in
+    // `buf`, where a negative size means "not yet resolved".
+    scan_stack: VecDeque<usize>,
+    right_total: isize,
+    left_total: isize,
+
+    // Indentation stack; one entry per currently-open Begin group, storing
+    // the indent to use for breaks inside it and whether it must be
+    // printed consistently.
+    print_stack: Vec<PrintFrame>,
+    // Running count used to assign each buffered token a stable id within
+    // `buf`/`scan_stack` bookkeeping.
+    next_id: usize,
+    ids: VecDeque<usize>,
+}
+
+#[derive(Clone, Copy)]
+struct PrintFrame {
+    indent: isize,
+    breaktype: BreakType,
+    // Whether the group this frame corresponds to was measured to fit on
+    // the current line.
+    fits: bool,
+}
+
+impl Printer {
+    pub fn new(margin: isize) -> Self {
+        Self {
+            margin,
+            space: margin,
+            out: String::new(),
+            buf: VecDeque::new(),
+            scan_stack: VecDeque::new(),
+            right_total: 0,
+            left_total: 0,
+            print_stack: Vec::new(),
+            next_id: 0,
+            ids: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, token: Token) {
+        match &token {
+            Token::Begin { .. } => {
+                if self.scan_stack.is_empty() {
+                    self.left_total = 1;
+                    self.right_total = 1;
+                    self.buf.clear();
+                    self.ids.clear();
+                }
+                let id = self.enqueue(token, -1);
+                self.scan_stack.push_back(id);
+            }
+            Token::End => {
+                if self.scan_stack.is_empty() {
+                    // No open group to match; print immediately.
+                    self.print_end();
+                } else {
+                    let id = self.enqueue(token, -1);
+                    self.scan_stack.push_back(id);
+                }
+            }
+            Token::Break { .. } => {
+                if self.scan_stack.is_empty() {
+                    self.left_total = 1;
+                    self.right_total = 1;
+                    self.buf.clear();
+                    self.ids.clear();
+                }
+                self.check_stack();
+                let id = self.enqueue(token.clone(), -1);
+                self.scan_stack.push_back(id);
+                self.right_total += token.flat_width();
+            }
+            Token::Text(_) => {
+                if self.scan_stack.is_empty() {
+                    self.print_text(&token);
+                } else {
+                    let width = token.flat_width();
+                    self.enqueue(token, width);
+                    self.right_total += width;
+                    self.check_stream();
+                }
+            }
+        }
+    }
+
+    fn enqueue(&mut self, token: Token, size: isize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.buf.push_back(BufEntry { token, size });
+        self.ids.push_back(id);
+        id
+    }
+
+    fn index_of(&self, id: usize) -> Option<usize> {
+        self.ids.iter().position(|&x| x == id)
+    }
+
+    fn check_stack(&mut self) {
+        // Resolve the sizes of any Begin/Break/End at the top of the scan
+        // stack now that we know a following token's contents.
+        while let Some(&top_id) = self.scan_stack.back() {
+            let Some(idx) = self.index_of(top_id) else {
+                self.scan_stack.pop_back();
+                continue;
+            };
+            match &self.buf[idx].token {
+                Token::Begin { .. } => {
+                    if self.buf[idx].size < 0 {
+                        break;
+                    }
+                    self.scan_stack.pop_back();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn check_stream(&mut self) {
+        while self.right_total - self.left_total > self.margin {
+            if let Some(&front_id) = self.scan_stack.front() {
+                let Some(front_idx) = self.index_of(front_id) else {
+                    self.scan_stack.pop_front();
+                    continue;
+                };
+                if front_idx == 0 {
+                    self.scan_stack.pop_front();
+                    self.buf[front_idx].size = isize::MAX;
+                } else {
+                    break;
+                }
+            } else {
+                break;
+            }
+            self.advance_left();
+        }
+    }
+
+    fn advance_left(&mut self) {
+        while let Some(front) = self.buf.front() {
+            if front.size < 0 {
+                break;
+            }
+            let entry = self.buf.pop_front().unwrap();
+            self.ids.pop_front();
+            self.left_total += entry.token.flat_width().max(0);
+            self.emit(entry.token, entry.size);
+        }
+    }
+
+    fn emit(&mut self, token: Token, size: isize) {
+        match token {
+            Token::Begin { indent, breaktype } => {
+                let fits = size >= 0 && size <= self.space;
+                self.print_stack.push(PrintFrame {
+                    indent,
+                    breaktype,
+                    fits,
+                });
+            }
+            Token::End => self.print_end(),
+            Token::Break { blank, indent } => self.print_break(blank, indent, size),
+            Token::Text(s) => self.print_text(&Token::Text(s)),
+        }
+    }
+
+    fn print_end(&mut self) {
+        self.print_stack.pop();
+    }
+
+    fn print_text(&mut self, token: &Token) {
+        if let Token::Text(s) = token {
+            self.out.push_str(s);
+            self.space -= s.chars().count() as isize;
+        }
+    }
+
+    fn print_break(&mut self, blank: usize, indent: isize, size: isize) {
+        let frame = self.print_stack.last().copied();
+        let fits = match frame {
+            Some(f) => match f.breaktype {
+                BreakType::Consistent => f.fits,
+                BreakType::Inconsistent => size >= 0 && size <= self.space,
+            },
+            None => true,
+        };
+
+        if fits {
+            self.space -= blank as isize;
+            self.out.push_str(&" ".repeat(blank));
+        } else {
+            let base_indent = frame.map(|f| f.indent).unwrap_or(0) + indent;
+            let base_indent = base_indent.max(0) as usize;
+            self.out.push('\n');
+            self.out.push_str(&" ".repeat(base_indent));
+            self.space = self.margin - base_indent as isize;
+        }
+    }
+
+    /// Flush any buffered tokens and return the finished document.
+    pub fn finish(mut self) -> String {
+        while !self.buf.is_empty() {
+            let entry = self.buf.pop_front().unwrap();
+            self.ids.pop_front();
+            let size = if entry.size < 0 {
+                isize::MAX
+            } else {
+                entry.size
+            };
+            self.emit(entry.token, size);
+        }
+        self.out
+    }
+}
+
+/// Render a token stream at the given margin.
+pub fn print(tokens: Vec<Token>, margin: isize) -> String {
+    let mut printer = Printer::new(margin);
+    for token in tokens {
+        printer.push(token);
+    }
+    printer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Token {
+        Token::Text(s.to_string())
+    }
+
+    fn space_break() -> Token {
+        Token::Break { blank: 1, indent: 0 }
+    }
+
+    #[test]
+    fn fits_on_one_line() {
+        let tokens = vec![
+            Token::Begin {
+                indent: 2,
+                breaktype: BreakType::Consistent,
+            },
+            text("a"),
+            space_break(),
+            text("b"),
+            Token::End,
+        ];
+        assert_eq!(print(tokens, 80), "a b");
+    }
+
+    #[test]
+    fn consistent_group_breaks_every_break() {
+        let long = "x".repeat(40);
+        let tokens = vec![
+            Token::Begin {
+                indent: 2,
+                breaktype: BreakType::Consistent,
+            },
+            text(&long),
+            space_break(),
+            text(&long),
+            space_break(),
+            text(&long),
+            Token::End,
+        ];
+        let out = print(tokens, 10);
+        assert_eq!(out.matches('\n').count(), 2);
+    }
+
+    #[test]
+    fn nested_groups_indent_correctly() {
+        let tokens = vec![
+            Token::Begin {
+                indent: 0,
+                breaktype: BreakType::Consistent,
+            },
+            text("outer("),
+            Token::Begin {
+                indent: 2,
+                breaktype: BreakType::Consistent,
+            },
+            text(&"y".repeat(30)),
+            space_break(),
+            text(&"y".repeat(30)),
+            Token::End,
+            text(")"),
+            Token::End,
+        ];
+        let out = print(tokens, 10);
+        assert!(out.contains('\n'));
+        assert!(out.lines().nth(1).unwrap().starts_with("  "));
+    }
+}
@@ -0,0 +1,11 @@
+//! Canonical source formatting for Weft programs.
+//!
+//! [`format_program`] lowers an [`crate::ast::Program`] into a token stream
+//! and renders it with an Oppen-style pretty-printing [`engine`], giving
+//! deterministic, idempotent output suitable for `weft fmt` and editor
+//! integration.
+
+pub mod ast_printer;
+pub mod engine;
+
+pub use ast_printer::format_program;
@@ -1,75 +1,438 @@
 use super::backend_registry::Context;
+use super::env::Superbeats;
 use super::render_graph::{MetaGraph, RenderGraph};
-use crate::ast::Program;
-use crate::backend::{Backend, DataRef};
+use super::scheduler::{ScheduledEvent, Scheduler};
+use crate::ast::{ASTNode, Program};
+use crate::backend::{AsyncBackend, Backend, CoordsBatch, DataRef, SyncAdapter, Transport};
 use crate::utils::Result;
 use crate::Env;
 use crate::WeftError;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::Poll;
 
+/// `backends`, `registry`, and `scheduler` are `Mutex`-guarded rather than
+/// `RefCell`-guarded so `&Coordinator` is `Sync` and can be shared across
+/// the worker threads `execute` spawns (or the futures `execute_async`
+/// drives) concurrently for one dependency level -- see `context_levels`
+/// below. `Backend: Send` (see
+/// `backend::types`) makes `Mutex<Box<dyn AsyncBackend>>` itself `Send`,
+/// so `Coordinator` as a whole satisfies `Sync` once every field does.
+/// Every backend -- whether registered via `add_backend` (wrapped in
+/// `SyncAdapter`) or `add_async_backend` directly -- lives in this one
+/// `AsyncBackend`-typed `backends` list; `AsyncBackend: Backend` means
+/// `compile_subgraph`/`execute_subgraph`/etc. still dispatch on it
+/// exactly as before, with no separate storage needed.
 pub struct Coordinator {
     render_graph: RenderGraph,
-    backends: RefCell<Vec<Box<dyn Backend>>>,
+    backends: Vec<Mutex<Box<dyn AsyncBackend>>>,
     meta_graph: Option<MetaGraph>,
     context_to_backend: HashMap<Context, usize>,
-    registry: RefCell<HashMap<String, usize>>,
+    registry: Mutex<HashMap<String, usize>>,
+    scheduler: Mutex<Scheduler>,
+    /// The transport `negotiate_transports` decided for each cross-context
+    /// `Reference`, keyed by `(from_node, to_node)`. Queried via
+    /// `transport_for` so tests and tooling can assert a graph runs
+    /// on-device rather than through a hidden scalar fallback.
+    transports: HashMap<(String, String), Transport>,
 }
 
 impl Coordinator {
     pub fn new() -> Self {
         Self {
             render_graph: RenderGraph::new(),
-            backends: RefCell::new(Vec::new()),
+            backends: Vec::new(),
             meta_graph: None,
             context_to_backend: HashMap::new(),
-            registry: RefCell::new(HashMap::new()),
+            registry: Mutex::new(HashMap::new()),
+            scheduler: Mutex::new(Scheduler::new()),
+            transports: HashMap::new(),
         }
     }
 
+    /// Registers a synchronous-only backend by wrapping it in `SyncAdapter`
+    /// -- `Coordinator`'s internal storage is `AsyncBackend` uniformly (see
+    /// `add_async_backend`), so `execute_pipelined`'s submit/poll mode
+    /// keeps working for it, with no real overlap to offer.
     pub fn add_backend(&mut self, backend: Box<dyn Backend>) {
+        self.add_async_backend(Box::new(SyncAdapter(backend)));
+    }
+
+    /// Registers a backend that implements the submit/poll `AsyncBackend`
+    /// surface itself, so `execute_pipelined` can let its work for one
+    /// dependency level overlap with the rest of that level's backends
+    /// instead of routing it through `SyncAdapter`'s submit-and-block.
+    pub fn add_async_backend(&mut self, backend: Box<dyn AsyncBackend>) {
         let context = backend.context();
-        let idx = self.backends.borrow().len();
-        self.backends.borrow_mut().push(backend);
+        let idx = self.backends.len();
+        self.backends.push(Mutex::new(backend));
         self.context_to_backend.insert(context, idx);
     }
 
+    /// Locks and returns the backend at `idx`, translating an out-of-range
+    /// index or a poisoned lock (some other thread panicked while holding
+    /// it) into the same `WeftError::Runtime` every call site already
+    /// raised for a bad index before backends lived behind a `Mutex`.
+    fn backend(&self, idx: usize) -> Result<MutexGuard<'_, Box<dyn AsyncBackend>>> {
+        self.backends
+            .get(idx)
+            .ok_or_else(|| WeftError::Runtime("Backend index out of bounds".to_string()))?
+            .lock()
+            .map_err(|_| WeftError::Runtime("Backend lock poisoned by a panicked thread".to_string()))
+    }
+
     pub fn compile(&mut self, ast: &Program, env: &Env) -> Result<()> {
-        let meta_graph = self.render_graph.build(ast, env)?;
+        let mut meta_graph = self.render_graph.build(ast, env)?;
+        self.prune_dead_subgraphs(ast, &mut meta_graph);
+        self.negotiate_transports(&meta_graph)?;
+
         for &subgraph_id in &meta_graph.execution_order {
-            let subgraph = &meta_graph.subgraphs[subgraph_id];
+            // A context pruned down to nothing by `prune_dead_subgraphs` is
+            // dropped from `subgraphs` entirely; skip it rather than
+            // dispatching an empty no-op compile.
+            let Some(subgraph) = meta_graph.subgraphs.get(&subgraph_id) else {
+                continue;
+            };
             let context = subgraph.context;
             let backend_idx = *self.context_to_backend.get(&context).ok_or_else(|| {
                 WeftError::Runtime(format!("No backend registered for context {:?}", context))
             })?;
 
-            self.backends
-                .borrow_mut()
-                .get_mut(backend_idx)
-                .ok_or_else(|| WeftError::Runtime("Backend index out of bounds".to_string()))?
+            self.backend(backend_idx)?
                 .compile_subgraph(subgraph, env, self)?;
         }
         self.meta_graph = Some(meta_graph);
         Ok(())
     }
 
+    /// Drops every instance whose outputs are all unreachable, backward,
+    /// from any `Backend` sink, per `Subgraph`, before `compile_subgraph`
+    /// is ever dispatched for it. Complements `RenderGraph::build`'s own
+    /// per-context BFS prune (which only sees each context's own sinks and
+    /// works at whole-instance granularity): this pass seeds its live set
+    /// from every sink across every context up front, at per-output
+    /// granularity, via `live_outputs`. A `Subgraph` pruned down to no
+    /// instances is removed from `meta_graph.subgraphs` outright.
+    fn prune_dead_subgraphs(&self, ast: &Program, meta_graph: &mut MetaGraph) {
+        let live = self.live_outputs(ast);
+
+        let mut emptied_contexts = Vec::new();
+        for (&context, subgraph) in meta_graph.subgraphs.iter_mut() {
+            let dead_names: HashSet<String> = subgraph
+                .node_names
+                .iter()
+                .filter(|name| {
+                    self.render_graph.get_node(name).is_some_and(|node| {
+                        !node.outputs.is_empty()
+                            && node
+                                .outputs
+                                .keys()
+                                .all(|output| !live.contains(&(name.to_string(), output.clone())))
+                    })
+                })
+                .cloned()
+                .collect();
+
+            if dead_names.is_empty() {
+                continue;
+            }
+
+            subgraph.node_names.retain(|name| !dead_names.contains(name));
+            subgraph.execution_order.retain(|name| !dead_names.contains(name));
+            for stage in &mut subgraph.stages {
+                stage.retain(|name| !dead_names.contains(name));
+            }
+            subgraph.stages.retain(|stage| !stage.is_empty());
+
+            let mut pruned = DiGraph::new();
+            let mut old_to_new = HashMap::new();
+            for idx in subgraph.graph.node_indices() {
+                if !dead_names.contains(&subgraph.graph[idx].instance_name) {
+                    old_to_new.insert(idx, pruned.add_node(subgraph.graph[idx].clone()));
+                }
+            }
+            for idx in subgraph.graph.node_indices() {
+                if let Some(&new_src) = old_to_new.get(&idx) {
+                    for edge in subgraph.graph.edges(idx) {
+                        if let Some(&new_tgt) = old_to_new.get(&edge.target()) {
+                            pruned.add_edge(new_src, new_tgt, ());
+                        }
+                    }
+                }
+            }
+            subgraph.graph = pruned;
+
+            if subgraph.node_names.is_empty() {
+                emptied_contexts.push(context);
+            }
+        }
+
+        for context in emptied_contexts {
+            meta_graph.subgraphs.remove(&context);
+        }
+    }
+
+    /// Decides, for every cross-context `meta_graph.references` edge,
+    /// whether the producer and the downstream consumer share a
+    /// `HandleKind`: if they do, the edge is recorded as `Transport::
+    /// Handle(kind)` -- a zero-copy read straight off the producer's
+    /// handle -- and otherwise as `Transport::ValueBridge`, the scalar
+    /// `get_value_at` round-trip through the CPU. Feedback edges (`meta_
+    /// graph.feedback`) are left out: they already go through double-
+    /// buffered storage for their one-frame delay, so there's no handle
+    /// to share regardless of what either backend advertises. Populates
+    /// `self.transports`, queried via `transport_for`.
+    fn negotiate_transports(&mut self, meta_graph: &MetaGraph) -> Result<()> {
+        self.transports.clear();
+
+        for reference in &meta_graph.references {
+            let Some(&producer_idx) = self.context_to_backend.get(&reference.from_context) else {
+                continue;
+            };
+            let Some(&consumer_idx) = self.context_to_backend.get(&reference.to_context) else {
+                continue;
+            };
+
+            let produced = self.backend(producer_idx)?.produced_handle_kinds();
+            let accepted = self.backend(consumer_idx)?.accepted_handle_kinds();
+            let shared = produced.into_iter().find(|kind| accepted.contains(kind));
+
+            let transport = match shared {
+                Some(kind) => Transport::Handle(kind),
+                None => Transport::ValueBridge,
+            };
+            self.transports
+                .insert((reference.from_node.clone(), reference.to_node.clone()), transport);
+        }
+
+        Ok(())
+    }
+
+    /// The `Transport` `negotiate_transports` decided for the cross-context
+    /// edge from `from_node` to `to_node`, or `None` if no such edge was
+    /// negotiated (not a cross-context reference, or `compile` hasn't run
+    /// yet). Lets tests and tooling assert a graph runs fully on-device by
+    /// checking every edge resolved to `Transport::Handle`.
+    pub fn transport_for(&self, from_node: &str, to_node: &str) -> Option<Transport> {
+        self.transports
+            .get(&(from_node.to_string(), to_node.to_string()))
+            .copied()
+    }
+
+    /// The `(instance, output)` pairs reachable backward from every
+    /// `Backend` statement's positional and named sink args, across every
+    /// context, via each node's `output_deps` (the same per-output
+    /// `StrandAccess` read-set `RenderGraph` records while typing). A
+    /// worklist with a visited set stands in for the reverse-topological
+    /// pass the request describes: it reaches a fixpoint in one pass over
+    /// a DAG and, because `live.contains` gates every push, never loops
+    /// forever even if a cycle is ever permitted -- the whole cycle just
+    /// ends up live, which is the same outcome a dedicated SCC collapse
+    /// would produce.
+    fn live_outputs(&self, ast: &Program) -> HashSet<(String, String)> {
+        let mut live: HashSet<(String, String)> = HashSet::new();
+        let mut frontier: Vec<(String, String)> = Vec::new();
+
+        for stmt in &ast.statements {
+            if let ASTNode::Backend(backend) = stmt {
+                for arg in backend
+                    .positional_args
+                    .iter()
+                    .chain(backend.named_args.values())
+                {
+                    collect_sink_reads(arg, &self.render_graph, &mut frontier);
+                }
+            }
+        }
+
+        while let Some(pair) = frontier.pop() {
+            if !live.insert(pair.clone()) {
+                continue;
+            }
+            let (instance, output) = &pair;
+            if let Some(deps) = self
+                .render_graph
+                .get_node(instance)
+                .and_then(|node| node.output_deps.get(output))
+            {
+                for dep in deps {
+                    if !live.contains(dep) {
+                        frontier.push(dep.clone());
+                    }
+                }
+            }
+        }
+
+        live
+    }
+
+    /// Runs every `Subgraph` in `meta_graph.execution_order`, one
+    /// dependency level at a time: `context_levels` groups contexts the
+    /// same way `Subgraph::stages` groups one context's nodes, and every
+    /// context within a level runs concurrently (via `std::thread::scope`)
+    /// since none of them can observe another level-mate's output. A level
+    /// with only one runnable context, or where any backend in it opts out
+    /// via `supports_parallel`, runs sequentially on the calling thread
+    /// instead -- the single-threaded fallback path for backends that opt
+    /// out. This is the default, synchronous entry point; `execute_async`
+    /// below is a separate, opt-in path for an embedder that wants to
+    /// overlap a backend's I/O with its own executor instead.
     pub fn execute(&self, env: &Env) -> Result<()> {
         let meta_graph = self.meta_graph.as_ref().ok_or_else(|| {
             WeftError::Runtime("Must call compile() before execute()".to_string())
         })?;
 
-        for &subgraph_id in &meta_graph.execution_order {
-            let subgraph = &meta_graph.subgraphs[subgraph_id];
-            let context = subgraph.context;
+        for level in context_levels(meta_graph) {
+            let runnable: Vec<Context> = level
+                .into_iter()
+                .filter(|context| meta_graph.subgraphs.contains_key(context))
+                .collect();
+
+            let all_parallel = runnable.len() > 1
+                && runnable.iter().all(|context| {
+                    self.context_to_backend
+                        .get(context)
+                        .and_then(|&idx| self.backend(idx).ok())
+                        .is_some_and(|backend| backend.supports_parallel())
+                });
+
+            if all_parallel {
+                std::thread::scope(|scope| -> Result<()> {
+                    let handles: Vec<_> = runnable
+                        .iter()
+                        .map(|&context| {
+                            scope.spawn(move || self.execute_context(context, meta_graph, env))
+                        })
+                        .collect();
+                    for handle in handles {
+                        handle.join().map_err(|_| {
+                            WeftError::Runtime(
+                                "a backend thread panicked during execute_subgraph".to_string(),
+                            )
+                        })??;
+                    }
+                    Ok(())
+                })?;
+            } else {
+                for context in runnable {
+                    self.execute_context(context, meta_graph, env)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dispatches one context's `execute_subgraph` call; shared by both
+    /// the parallel and sequential paths in `execute`.
+    fn execute_context(&self, context: Context, meta_graph: &MetaGraph, env: &Env) -> Result<()> {
+        let subgraph = &meta_graph.subgraphs[&context];
+        let backend_idx = *self.context_to_backend.get(&context).ok_or_else(|| {
+            WeftError::Runtime(format!("No backend registered for context {:?}", context))
+        })?;
+
+        self.backend(backend_idx)?.execute_subgraph(subgraph, env, self)
+    }
+
+    /// Async counterpart to `execute`, for an embedder that already has
+    /// its own executor to poll this on and wants a backend's
+    /// `execute_subgraph_async` to overlap its I/O with another backend's
+    /// compute in the same dependency level, instead of either blocking a
+    /// worker thread for the duration. Not used by `execute` itself --
+    /// `std::thread::scope` is still the default concurrency mechanism for
+    /// callers (the CLI, tests) with no executor of their own to drive a
+    /// future on. Same `context_levels` dependency-level grouping as
+    /// `execute`; every context within a level is driven concurrently as a
+    /// future (via `join_all`) rather than spawned onto its own thread.
+    pub async fn execute_async(&self, env: &Env) -> Result<()> {
+        let meta_graph = self.meta_graph.as_ref().ok_or_else(|| {
+            WeftError::Runtime("Must call compile() before execute()".to_string())
+        })?;
+
+        for level in context_levels(meta_graph) {
+            let futures: Vec<Pin<Box<dyn Future<Output = Result<()>> + '_>>> = level
+                .into_iter()
+                .filter(|context| meta_graph.subgraphs.contains_key(context))
+                .map(|context| -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+                    Box::pin(self.execute_context_async(context, meta_graph, env))
+                })
+                .collect();
+
+            join_all(futures).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Dispatches one context's `execute_subgraph_async` call. An `async
+    /// move` block so the `MutexGuard` it locks the backend behind can
+    /// live inside the returned future's own state across the `.await`,
+    /// the same self-borrowing any `async fn` supports for its own
+    /// locals.
+    fn execute_context_async<'a>(
+        &'a self,
+        context: Context,
+        meta_graph: &'a MetaGraph,
+        env: &'a Env,
+    ) -> impl Future<Output = Result<()>> + 'a {
+        async move {
+            let subgraph = &meta_graph.subgraphs[&context];
             let backend_idx = *self.context_to_backend.get(&context).ok_or_else(|| {
                 WeftError::Runtime(format!("No backend registered for context {:?}", context))
             })?;
 
-            self.backends
-                .borrow_mut()
-                .get_mut(backend_idx)
-                .ok_or_else(|| WeftError::Runtime("Backend index out of bounds".to_string()))?
-                .execute_subgraph(subgraph, env, self)?;
+            let mut backend = self.backend(backend_idx)?;
+            backend.execute_subgraph_async(subgraph, env, self).await
+        }
+    }
+
+    /// Submit/poll counterpart to `execute`/`execute_async`: same
+    /// `context_levels` dependency-level grouping, but every context in a
+    /// level is submitted via `AsyncBackend::submit_subgraph` up front,
+    /// before any of them are polled to completion -- so e.g. the visual
+    /// backend's next frame can start submitting while audio is still
+    /// draining the current one, instead of the two strictly alternating.
+    /// A backend registered through the plain `add_backend` still works
+    /// here, via the `SyncAdapter` it's implicitly wrapped in.
+    pub fn execute_pipelined(&self, env: &Env) -> Result<()> {
+        let meta_graph = self.meta_graph.as_ref().ok_or_else(|| {
+            WeftError::Runtime("Must call compile() before execute()".to_string())
+        })?;
+
+        for level in context_levels(meta_graph) {
+            let runnable: Vec<Context> = level
+                .into_iter()
+                .filter(|context| meta_graph.subgraphs.contains_key(context))
+                .collect();
+
+            let mut submitted = Vec::with_capacity(runnable.len());
+            for context in runnable {
+                let subgraph = &meta_graph.subgraphs[&context];
+                let backend_idx = *self.context_to_backend.get(&context).ok_or_else(|| {
+                    WeftError::Runtime(format!("No backend registered for context {:?}", context))
+                })?;
+                let token = self
+                    .backend(backend_idx)?
+                    .submit_subgraph(subgraph, env, self)?;
+                submitted.push((backend_idx, token));
+            }
+
+            for (backend_idx, token) in submitted {
+                loop {
+                    match self.backend(backend_idx)?.poll_submit(token) {
+                        Poll::Ready(result) => {
+                            result?;
+                            break;
+                        }
+                        Poll::Pending => std::thread::yield_now(),
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -78,24 +441,22 @@ impl Coordinator {
     pub fn expose(&self, instance: &str, output: &str, context: Context) {
         let key = format!("{}@{}", instance, output);
         if let Some(&backend_idx) = self.context_to_backend.get(&context) {
-            self.registry.borrow_mut().insert(key, backend_idx);
+            self.registry.lock().unwrap().insert(key, backend_idx);
         }
     }
 
     pub fn lookup<'a>(&'a self, instance: &str, output: &str) -> Result<DataRef<'a>> {
         let key = format!("{}@{}", instance, output);
-        let backend_idx = *self.registry.borrow().get(&key).ok_or_else(|| {
+        let backend_idx = *self.registry.lock().unwrap().get(&key).ok_or_else(|| {
             WeftError::Runtime(format!("No backend registered for {}@{}", instance, output))
         })?;
 
-        let backends = self.backends.borrow();
-        let backend = backends
-            .get(backend_idx)
-            .ok_or_else(|| WeftError::Runtime("Backend index out of bounds".to_string()))?;
-
-        if backend.supports_handles() {
-            if let Ok(handle) = backend.get_handle(instance, output) {
-                return Ok(DataRef::Handle(handle));
+        {
+            let backend = self.backend(backend_idx)?;
+            if backend.supports_handles() {
+                if let Ok(handle) = backend.get_handle(instance, output) {
+                    return Ok(DataRef::Handle(handle));
+                }
             }
         }
 
@@ -104,15 +465,96 @@ impl Coordinator {
 
         Ok(DataRef::ValueGetter(Box::new(
             move |coords: &HashMap<String, f64>, env: &Env, coordinator: &Coordinator| {
-                let backends = coordinator.backends.borrow();
-                let backend = backends
-                    .get(backend_idx)
-                    .ok_or_else(|| WeftError::Runtime("Backend index out of bounds".to_string()))?;
+                let backend = coordinator.backend(backend_idx)?;
                 backend.get_value_at(&instance_owned, &output_owned, coords, env, coordinator)
             },
         )))
     }
 
+    /// Batched counterpart to `lookup`: same handle-vs-value dispatch,
+    /// but the value case hands back a `DataRef::BatchGetter` wrapping
+    /// `Backend::get_values_batch` instead of `get_value_at`, for a caller
+    /// evaluating many points (a whole scanline, say) at once.
+    pub fn lookup_batch<'a>(&'a self, instance: &str, output: &str) -> Result<DataRef<'a>> {
+        let key = format!("{}@{}", instance, output);
+        let backend_idx = *self.registry.lock().unwrap().get(&key).ok_or_else(|| {
+            WeftError::Runtime(format!("No backend registered for {}@{}", instance, output))
+        })?;
+
+        {
+            let backend = self.backend(backend_idx)?;
+            if backend.supports_handles() {
+                if let Ok(handle) = backend.get_handle(instance, output) {
+                    return Ok(DataRef::Handle(handle));
+                }
+            }
+        }
+
+        let instance_owned = instance.to_string();
+        let output_owned = output.to_string();
+
+        Ok(DataRef::BatchGetter(Box::new(
+            move |coords_batch: &CoordsBatch, env: &Env, coordinator: &Coordinator, out: &mut [f64]| {
+                let backend = coordinator.backend(backend_idx)?;
+                backend.get_values_batch(
+                    &instance_owned,
+                    &output_owned,
+                    coords_batch,
+                    env,
+                    coordinator,
+                    out,
+                )
+            },
+        )))
+    }
+
+    /// Queues `event` to be dispatched once its `at_beat` falls inside a
+    /// future run-ahead window (see `dispatch_scheduled_events`).
+    pub fn schedule_event(&self, event: ScheduledEvent) {
+        self.scheduler.lock().unwrap().schedule(event);
+    }
+
+    /// Pops every event due in `[env.current_beat(), env.current_beat() +
+    /// lookahead]` and hands each to the backend that owns its
+    /// `(instance, output)` target, in `Context::priority` order (Visual,
+    /// then Audio, then Compute) so events due on the same beat apply in
+    /// a consistent order across contexts. Meant to be called once per
+    /// `env.sync_counters()` tick, the same way `compile`/`execute` are
+    /// called once per build/frame.
+    pub fn dispatch_scheduled_events(&self, env: &Env, lookahead: Superbeats) -> Result<()> {
+        let now = Superbeats::from_beats(env.current_beat());
+        let due = self.scheduler.lock().unwrap().drain_due(now, lookahead);
+
+        let mut due_by_backend = Vec::with_capacity(due.len());
+        for event in due {
+            let key = format!("{}@{}", event.instance, event.output);
+            let backend_idx = *self.registry.lock().unwrap().get(&key).ok_or_else(|| {
+                WeftError::Runtime(format!(
+                    "No backend registered for {}@{}",
+                    event.instance, event.output
+                ))
+            })?;
+            due_by_backend.push((backend_idx, event));
+        }
+
+        due_by_backend.sort_by_key(|(backend_idx, _)| {
+            self.backend(*backend_idx)
+                .map(|backend| backend.context().priority())
+                .unwrap_or(u32::MAX)
+        });
+
+        for (backend_idx, event) in due_by_backend {
+            let at_beat = event.at_beat;
+            let instance = event.instance.clone();
+            let output = event.output.clone();
+            let value = event.value.resolve();
+            self.backend(backend_idx)?
+                .schedule_value_change(&instance, &output, at_beat, value)?;
+        }
+
+        Ok(())
+    }
+
     pub fn render_graph(&self) -> &RenderGraph {
         &self.render_graph
     }
@@ -120,6 +562,116 @@ impl Coordinator {
     pub fn meta_graph(&self) -> Option<&MetaGraph> {
         self.meta_graph.as_ref()
     }
+
+    /// Dumps the compiled dependency structure as Graphviz `digraph`
+    /// source, for pasting into any DOT viewer. Unlike `RenderGraph`'s own
+    /// node-level graph, each node here is one `instance@output` pair --
+    /// the same granularity `lookup` resolves -- clustered and colored by
+    /// `Context` so a cross-context edge (the thing `test_lookup_cross_context`
+    /// and the audio-visual-audio chain build) is visually obvious, and
+    /// labeled with whether `lookup` currently resolves it to a `Handle`
+    /// or a `ValueGetter`. Edges are each output's `output_deps`, the same
+    /// per-output `StrandAccess` read-set `live_outputs` walks. Returns an
+    /// empty digraph if called before `compile` has populated a subgraph
+    /// list, rather than failing -- a visualization aid has no failure
+    /// mode worth surfacing to the caller.
+    pub fn to_dot(&self) -> String {
+        use std::fmt::Write as _;
+
+        let mut out = String::new();
+        writeln!(out, "digraph weft {{").unwrap();
+        writeln!(out, "    rankdir=LR;").unwrap();
+        writeln!(out, "    node [style=filled];").unwrap();
+
+        let Some(meta_graph) = self.meta_graph.as_ref() else {
+            out.push_str("}\n");
+            return out;
+        };
+
+        for (cluster_idx, &context) in meta_graph.execution_order.iter().enumerate() {
+            let Some(subgraph) = meta_graph.subgraphs.get(&context) else {
+                continue;
+            };
+
+            writeln!(out).unwrap();
+            writeln!(out, "    subgraph cluster_{} {{", cluster_idx).unwrap();
+            writeln!(out, "        label={};", dot_str(context.name())).unwrap();
+            writeln!(out, "        style=dashed;").unwrap();
+            writeln!(out, "        node [fillcolor={}];", context_color(context)).unwrap();
+
+            for name in &subgraph.node_names {
+                let Some(node) = self.render_graph.get_node(name) else {
+                    continue;
+                };
+                for output in node.outputs.keys() {
+                    let kind = match self.lookup(name, output) {
+                        Ok(DataRef::Handle(_)) => "handle",
+                        Ok(DataRef::ValueGetter(_)) => "value",
+                        Ok(DataRef::BatchGetter(_)) => "value",
+                        Err(_) => "unexposed",
+                    };
+                    writeln!(
+                        out,
+                        "        {} [label={}];",
+                        dot_id(name, output),
+                        dot_str(&format!("{}@{}\\n({})", name, output, kind)),
+                    )
+                    .unwrap();
+                }
+            }
+
+            writeln!(out, "    }}").unwrap();
+        }
+
+        writeln!(out).unwrap();
+        for &context in &meta_graph.execution_order {
+            let Some(subgraph) = meta_graph.subgraphs.get(&context) else {
+                continue;
+            };
+            for name in &subgraph.node_names {
+                let Some(node) = self.render_graph.get_node(name) else {
+                    continue;
+                };
+                for (output, deps) in &node.output_deps {
+                    for (dep_instance, dep_output) in deps {
+                        writeln!(
+                            out,
+                            "    {} -> {};",
+                            dot_id(dep_instance, dep_output),
+                            dot_id(name, output),
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Quotes and escapes `id` as a single Graphviz node identifier naming
+/// one `instance@output` pair.
+fn dot_id(instance: &str, output: &str) -> String {
+    dot_str(&format!("{}@{}", instance, output))
+}
+
+/// Quotes and backslash-escapes `s` as a Graphviz string literal (a node
+/// id, a label, ...).
+fn dot_str(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Fill color for `to_dot`'s per-`Context` clusters, distinct from
+/// `NodeType`'s own palette in the CLI's `render_graph_dot` since these
+/// are two different graphs (instances vs. instance outputs).
+fn context_color(context: Context) -> &'static str {
+    match context {
+        Context::Visual => "lightblue",
+        Context::Audio => "lightpink",
+        Context::Compute => "lightgoldenrod",
+    }
 }
 
 impl Default for Coordinator {
@@ -127,3 +679,140 @@ impl Default for Coordinator {
         Self::new()
     }
 }
+
+/// Groups `meta_graph.context_dag`'s contexts into dependency levels the
+/// same way `RenderGraph::extract_subgraphs` groups one context's nodes
+/// into `Subgraph::stages`: level 0 is every context with no upstream
+/// context, and level N+1 is every context whose deepest upstream
+/// dependency sits at level N. Walking `meta_graph.execution_order` (a
+/// valid topological order already) guarantees every predecessor's level
+/// is computed before it's needed, just like the node-level version does.
+fn context_levels(meta_graph: &MetaGraph) -> Vec<Vec<Context>> {
+    let dag = &meta_graph.context_dag;
+    let idx_by_context: HashMap<Context, NodeIndex> =
+        dag.node_indices().map(|idx| (dag[idx], idx)).collect();
+
+    let mut levels: HashMap<NodeIndex, usize> = HashMap::new();
+    for context in &meta_graph.execution_order {
+        let Some(&idx) = idx_by_context.get(context) else {
+            continue;
+        };
+        let level = dag
+            .neighbors_directed(idx, Direction::Incoming)
+            .map(|pred| levels[&pred] + 1)
+            .max()
+            .unwrap_or(0);
+        levels.insert(idx, level);
+    }
+
+    let stage_count = levels.values().copied().max().map_or(0, |m| m + 1);
+    let mut stages: Vec<Vec<Context>> = vec![Vec::new(); stage_count];
+    for context in &meta_graph.execution_order {
+        if let Some(&idx) = idx_by_context.get(context) {
+            stages[levels[&idx]].push(*context);
+        }
+    }
+    stages
+}
+
+/// Polls every future in `futures` round-robin until all have resolved,
+/// short-circuiting on the first error -- a hand-rolled stand-in for
+/// `futures::future::join_all` since this crate has no dependency on an
+/// async runtime. Used by `execute_async` to join one dependency level's
+/// worth of concurrent `execute_subgraph_async` calls.
+fn join_all<'a>(
+    mut futures: Vec<Pin<Box<dyn Future<Output = Result<()>> + 'a>>>,
+) -> impl Future<Output = Result<()>> + 'a {
+    std::future::poll_fn(move |cx| {
+        let mut idx = 0;
+        let mut pending = false;
+        while idx < futures.len() {
+            match futures[idx].as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => {
+                    futures.remove(idx);
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => {
+                    pending = true;
+                    idx += 1;
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    })
+}
+
+/// Walks one `Backend` sink argument expression, collecting every
+/// `(instance, output)` pair it reads, for `Coordinator::live_outputs` to
+/// seed its backward dataflow from. Mirrors the shape of `RenderGraph`'s
+/// internal `find_output_deps_in_expr`, plus one extra case: a bare
+/// instance name (`play(audio)`) doesn't say which of its outputs it
+/// wants, so this conservatively reads all of them rather than risk
+/// pruning one a backend actually consumes as a whole strand.
+fn collect_sink_reads(expr: &ASTNode, render_graph: &RenderGraph, out: &mut Vec<(String, String)>) {
+    match expr {
+        ASTNode::Var(var) => {
+            if let Some(node) = render_graph.get_node(var.name.resolve()) {
+                out.extend(
+                    node.outputs
+                        .keys()
+                        .map(|output| (var.name.resolve().to_string(), output.clone())),
+                );
+            }
+        }
+        ASTNode::StrandAccess(access) => {
+            if let (ASTNode::Var(base), ASTNode::Var(field)) = (&*access.base, &*access.out) {
+                out.push((base.name.resolve().to_string(), field.name.resolve().to_string()));
+            }
+        }
+        ASTNode::StrandRemap(remap) => {
+            if let ASTNode::Var(base) = &*remap.base {
+                if let Some(node) = render_graph.get_node(base.name.resolve()) {
+                    out.extend(
+                        node.outputs
+                            .keys()
+                            .map(|output| (base.name.resolve().to_string(), output.clone())),
+                    );
+                }
+            }
+            for mapping in &remap.mappings {
+                collect_sink_reads(&mapping.expr, render_graph, out);
+            }
+        }
+        ASTNode::Binary(bin) => {
+            collect_sink_reads(&bin.left, render_graph, out);
+            collect_sink_reads(&bin.right, render_graph, out);
+        }
+        ASTNode::Unary(un) => collect_sink_reads(&un.expr, render_graph, out),
+        ASTNode::Call(call) => {
+            for arg in &call.args {
+                collect_sink_reads(arg, render_graph, out);
+            }
+        }
+        ASTNode::If(if_expr) => {
+            collect_sink_reads(&if_expr.condition, render_graph, out);
+            collect_sink_reads(&if_expr.then_expr, render_graph, out);
+            collect_sink_reads(&if_expr.else_expr, render_graph, out);
+        }
+        ASTNode::Match(match_expr) => {
+            collect_sink_reads(&match_expr.scrutinee, render_graph, out);
+            for arm in &match_expr.arms {
+                collect_sink_reads(&arm.body, render_graph, out);
+            }
+        }
+        ASTNode::Tuple(tuple) => {
+            for item in &tuple.items {
+                collect_sink_reads(item, render_graph, out);
+            }
+        }
+        ASTNode::Index(index) => {
+            collect_sink_reads(&index.base, render_graph, out);
+            collect_sink_reads(&index.index, render_graph, out);
+        }
+        _ => {}
+    }
+}
@@ -163,6 +163,9 @@ mod tests {
             DataRef::Handle(_) => {
                 panic!("CPU backend should not return a handle");
             }
+            DataRef::BatchGetter(_) => {
+                panic!("lookup() should never return a BatchGetter");
+            }
         }; // Semicolon ensures temporary is dropped before coordinator
     }
 
@@ -184,6 +187,9 @@ mod tests {
             DataRef::ValueGetter(_) => {
                 panic!("GPU backend with handle support should return a handle");
             }
+            DataRef::BatchGetter(_) => {
+                panic!("lookup() should never return a BatchGetter");
+            }
         }; // Semicolon ensures temporary is dropped before coordinator
     }
 
@@ -213,41 +219,45 @@ mod tests {
             DataRef::Handle(_) => {
                 panic!("CPU backend should not return a handle");
             }
+            DataRef::BatchGetter(_) => {
+                panic!("lookup() should never return a BatchGetter");
+            }
         }; // Semicolon ensures temporary is dropped before coordinator
     }
 
-    // Tracking backend that records compilation and execution order
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    // Tracking backend that records compilation and execution order. Uses
+    // `Arc<Mutex<_>>` rather than `Rc<RefCell<_>>` so `TrackingBackend`
+    // stays `Send`, same as any real `Backend` impl now must be.
+    use std::sync::{Arc, Mutex};
 
     #[derive(Clone)]
     struct ExecutionLog {
-        compile_calls: Rc<RefCell<Vec<(Context, Vec<String>)>>>,
-        execute_calls: Rc<RefCell<Vec<(Context, Vec<String>)>>>,
+        compile_calls: Arc<Mutex<Vec<(Context, Vec<String>)>>>,
+        execute_calls: Arc<Mutex<Vec<(Context, Vec<String>)>>>,
     }
 
     impl ExecutionLog {
         fn new() -> Self {
             Self {
-                compile_calls: Rc::new(RefCell::new(Vec::new())),
-                execute_calls: Rc::new(RefCell::new(Vec::new())),
+                compile_calls: Arc::new(Mutex::new(Vec::new())),
+                execute_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
         fn record_compile(&self, context: Context, nodes: Vec<String>) {
-            self.compile_calls.borrow_mut().push((context, nodes));
+            self.compile_calls.lock().unwrap().push((context, nodes));
         }
 
         fn record_execute(&self, context: Context, nodes: Vec<String>) {
-            self.execute_calls.borrow_mut().push((context, nodes));
+            self.execute_calls.lock().unwrap().push((context, nodes));
         }
 
         fn get_compile_calls(&self) -> Vec<(Context, Vec<String>)> {
-            self.compile_calls.borrow().clone()
+            self.compile_calls.lock().unwrap().clone()
         }
 
         fn get_execute_calls(&self) -> Vec<(Context, Vec<String>)> {
-            self.execute_calls.borrow().clone()
+            self.execute_calls.lock().unwrap().clone()
         }
     }
 
@@ -326,30 +336,38 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "visual_node".to_string(),
                     outputs: vec!["color".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0, kind: NumKind::Int(1), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio_node".to_string(),
                     outputs: vec!["freq".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0, kind: NumKind::Int(440), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual_node".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "color".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual_node"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("color"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio_node".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio_node"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -393,50 +411,66 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio1".to_string(),
                     outputs: vec!["freq".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0, kind: NumKind::Int(440), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "visual".to_string(),
                     outputs: vec!["color".to_string()],
                     expr: Box::new(ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio1".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio1"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio2".to_string(),
                     outputs: vec!["amp".to_string()],
                     expr: Box::new(ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "color".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("color"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio1".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio1"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "color".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("color"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio2".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "amp".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio2"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("amp"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -484,16 +518,20 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "visual_node".to_string(),
                     outputs: vec!["color".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0, kind: NumKind::Int(1), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual_node".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "color".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual_node"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("color"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -538,30 +576,38 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio1".to_string(),
                     outputs: vec!["freq".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 440.0, kind: NumKind::Int(440), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio2".to_string(),
                     outputs: vec!["freq".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 880.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 880.0, kind: NumKind::Int(880), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio1".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio1"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio2".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio2"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -605,16 +651,20 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "node1".to_string(),
                     outputs: vec!["output1".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0, kind: NumKind::Int(1), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "node1".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "output1".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("node1"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("output1"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -643,33 +693,43 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "visual_source".to_string(),
                     outputs: vec!["brightness".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 0.5 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 0.5, kind: NumKind::Float(0.5), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "audio_out".to_string(),
                     outputs: vec!["freq".to_string()],
                     expr: Box::new(ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual_source".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "brightness".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual_source"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("brightness"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "visual_source".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "brightness".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("visual_source"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("brightness"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "play".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "audio_out".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "freq".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("audio_out"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("freq"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -696,16 +756,20 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "test_node".to_string(),
                     outputs: vec!["value".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 42.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 42.0, kind: NumKind::Int(42), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "test_node".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "value".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("test_node"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("value"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -725,6 +789,9 @@ mod tests {
             DataRef::Handle(_) => {
                 panic!("TrackingBackend should return ValueGetter, not Handle");
             }
+            DataRef::BatchGetter(_) => {
+                panic!("lookup() should never return a BatchGetter");
+            }
         }; // Semicolon ensures temporary is dropped before coordinator
     }
 
@@ -743,16 +810,20 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "node1".to_string(),
                     outputs: vec!["out1".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0, kind: NumKind::Int(1), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "node1".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "out1".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("node1"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("out1"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -832,16 +903,20 @@ mod tests {
                 ASTNode::InstanceBinding(InstanceBindExpr {
                     name: "multi_node".to_string(),
                     outputs: vec!["a".to_string(), "b".to_string(), "c".to_string()],
-                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0 })),
+                    expr: Box::new(ASTNode::Num(NumExpr { v: 1.0, kind: NumKind::Int(1), span: Span::synthetic() })),
+                    span: Span::synthetic(),
                 }),
                 ASTNode::Backend(BackendExpr {
                     context: "display".to_string(),
                     args: vec![],
                     named_args: HashMap::new(),
                     positional_args: vec![ASTNode::StrandAccess(StrandAccessExpr {
-                        base: Box::new(ASTNode::Var(VarExpr { name: "multi_node".to_string() })),
-                        out: Box::new(ASTNode::Var(VarExpr { name: "a".to_string() })),
+                        base: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("multi_node"), span: Span::synthetic() })),
+                        out: Box::new(ASTNode::Var(VarExpr { name: crate::symbol::intern("a"), span: Span::synthetic() })),
+                        delayed: false,
+                        span: Span::synthetic(),
                     })],
+                    span: Span::synthetic(),
                 }),
             ],
         };
@@ -0,0 +1,297 @@
+use super::backend_registry::Context;
+use super::render_graph::{GraphNode, MetaGraph};
+use crate::ast::Span;
+use std::collections::HashMap;
+
+/// A location where execution should pause, matched against a node's
+/// source span. `line` is 1-based and resolved against the source text
+/// passed to `Debugger::new`.
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+/// One step of a debugging session: the context/instance that just ran,
+/// and the bindings visible after it.
+#[derive(Debug, Clone)]
+pub struct StepInfo {
+    pub context: Context,
+    pub instance_name: String,
+    pub span: Option<Span>,
+    pub bindings: HashMap<String, f64>,
+}
+
+/// Steps through a compiled `MetaGraph` one node at a time, in the same
+/// per-context topological order the `Coordinator` would execute it in.
+///
+/// Weft has no expression-level tree-walking evaluator (evaluation happens
+/// per backend over compiled subgraphs), so "single-stepping" here means
+/// one graph node at a time rather than one sub-expression at a time.
+/// Breakpoints match a node's source span against a (file, line); bindings
+/// are a placeholder view of the most recently computed numeric outputs
+/// (always `0.0`, since there is no `Value` type yet to inspect richer
+/// results, and no evaluator to compute a real one from).
+///
+/// This is library-only plumbing: nothing in `main.rs` constructs a
+/// `Debugger` yet, so there is no `weft debug` (or equivalent REPL) entry
+/// point a user could actually drive this from today.
+pub struct Debugger<'a> {
+    meta_graph: &'a MetaGraph,
+    source: &'a str,
+    steps: Vec<(Context, String)>,
+    cursor: usize,
+    breakpoints: Vec<Breakpoint>,
+    bindings: HashMap<String, f64>,
+}
+
+impl<'a> Debugger<'a> {
+    /// `source` is the program text the `MetaGraph` was compiled from; it
+    /// is only needed to resolve breakpoint line numbers against node
+    /// spans, which store byte offsets.
+    pub fn new(meta_graph: &'a MetaGraph, source: &'a str) -> Self {
+        let mut steps = Vec::new();
+        for &context in &meta_graph.execution_order {
+            let subgraph = &meta_graph.subgraphs[&context];
+            for name in &subgraph.execution_order {
+                steps.push((context, name.clone()));
+            }
+        }
+
+        Self {
+            meta_graph,
+            source,
+            steps,
+            cursor: 0,
+            breakpoints: Vec::new(),
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, file: Option<String>, line: usize) {
+        self.breakpoints.push(Breakpoint { file, line });
+    }
+
+    /// Looks up the node about to execute, if any steps remain.
+    pub fn peek(&self) -> Option<&GraphNode> {
+        let (context, name) = self.steps.get(self.cursor)?;
+        self.node_named(*context, name)
+    }
+
+    fn node_named(&self, context: Context, name: &str) -> Option<&GraphNode> {
+        self.meta_graph.subgraphs[&context]
+            .graph
+            .node_weights()
+            .find(|node| node.instance_name == *name)
+    }
+
+    /// The span of the current node, preferring its first output
+    /// expression's span as the representative location.
+    fn current_span(&self) -> Option<Span> {
+        let node = self.peek()?;
+        node.outputs.values().find_map(|expr| expr.span().cloned())
+    }
+
+    /// Advances one graph node, recording a placeholder numeric binding
+    /// for each of its outputs (0.0, since there is no evaluator here yet)
+    /// and returning info about the step that was just taken.
+    pub fn step(&mut self) -> Option<StepInfo> {
+        let (context, name) = self.steps.get(self.cursor)?.clone();
+        let span = self.current_span();
+        let node = self.node_named(context, &name)?;
+
+        for output_name in node.outputs.keys() {
+            self.bindings
+                .insert(format!("{}@{}", name, output_name), 0.0);
+        }
+
+        let info = StepInfo {
+            context,
+            instance_name: name,
+            span,
+            bindings: self.bindings.clone(),
+        };
+
+        self.cursor += 1;
+        Some(info)
+    }
+
+    /// Runs until the next breakpoint is hit or execution finishes,
+    /// returning the step that stopped on a breakpoint (if any).
+    pub fn run_to_breakpoint(&mut self) -> Option<StepInfo> {
+        while self.cursor < self.steps.len() {
+            let hit = self.current_span().is_some_and(|span| self.matches_breakpoint(&span));
+            let info = self.step()?;
+            if hit {
+                return Some(info);
+            }
+        }
+        None
+    }
+
+    fn matches_breakpoint(&self, span: &Span) -> bool {
+        let line = line_of(self.source, span.start);
+        self.breakpoints
+            .iter()
+            .any(|bp| bp.file == span.file && bp.line == line)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cursor >= self.steps.len()
+    }
+
+    /// The placeholder bindings visible at the current point in execution.
+    pub fn bindings(&self) -> &HashMap<String, f64> {
+        &self.bindings
+    }
+}
+
+/// Resolves a byte offset to a 1-based line number within `source`.
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+    use crate::runtime::{Coordinator, Env};
+    use crate::parser;
+    use crate::utils::Result;
+
+    /// Bare-bones `Backend` that compiles/executes without doing anything,
+    /// just enough for `Coordinator::compile` to produce a `MetaGraph` to
+    /// step through.
+    struct NoopBackend {
+        context: Context,
+    }
+
+    impl Backend for NoopBackend {
+        fn context(&self) -> Context {
+            self.context
+        }
+
+        fn supports_handles(&self) -> bool {
+            false
+        }
+
+        fn compile_subgraph(
+            &mut self,
+            _subgraph: &super::super::render_graph::Subgraph,
+            _env: &Env,
+            _coordinator: &Coordinator,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn execute_subgraph(
+            &mut self,
+            _subgraph: &super::super::render_graph::Subgraph,
+            _env: &Env,
+            _coordinator: &Coordinator,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_value_at(
+            &self,
+            _instance: &str,
+            _output: &str,
+            _coords: &HashMap<String, f64>,
+            _env: &Env,
+            _coordinator: &Coordinator,
+        ) -> Result<f64> {
+            Ok(0.0)
+        }
+    }
+
+    /// Parses and compiles `source` into a `Coordinator` with a `NoopBackend`
+    /// for every context it uses, returning it so a test can pull its
+    /// `MetaGraph` out for a `Debugger`.
+    fn compile(source: &str) -> Coordinator {
+        let ast = parser::parse(source).unwrap();
+        let mut coordinator = Coordinator::new();
+        coordinator.add_backend(Box::new(NoopBackend { context: Context::Audio }));
+        coordinator.add_backend(Box::new(NoopBackend { context: Context::Visual }));
+        coordinator.add_backend(Box::new(NoopBackend { context: Context::Compute }));
+        let env = Env::new(100, 100);
+        coordinator.compile(&ast, &env).unwrap();
+        coordinator
+    }
+
+    #[test]
+    fn steps_every_node_then_reports_done() {
+        let source = "x<a> = 1\ny<a> = x@a\nplay(y@a)";
+        let coordinator = compile(source);
+        let meta_graph = coordinator.meta_graph().unwrap();
+        let mut debugger = Debugger::new(meta_graph, source);
+
+        assert!(!debugger.is_done());
+        let mut stepped = Vec::new();
+        while let Some(info) = debugger.step() {
+            stepped.push(info.instance_name);
+        }
+
+        assert!(debugger.is_done());
+        assert!(stepped.contains(&"x".to_string()));
+        assert!(stepped.contains(&"y".to_string()));
+        // x must be stepped before y, since y reads x@a.
+        let x_pos = stepped.iter().position(|n| n == "x").unwrap();
+        let y_pos = stepped.iter().position(|n| n == "y").unwrap();
+        assert!(x_pos < y_pos);
+    }
+
+    #[test]
+    fn step_records_a_binding_for_every_output() {
+        let source = "x<a> = 1\nplay(x@a)";
+        let coordinator = compile(source);
+        let meta_graph = coordinator.meta_graph().unwrap();
+        let mut debugger = Debugger::new(meta_graph, source);
+
+        let info = debugger.step().unwrap();
+        assert_eq!(info.instance_name, "x");
+        assert!(info.bindings.contains_key("x@a"));
+        assert_eq!(debugger.bindings().get("x@a"), Some(&0.0));
+    }
+
+    #[test]
+    fn run_to_breakpoint_stops_on_matching_line() {
+        let source = "x<a> = 1\ny<a> = x@a\nz<a> = y@a\nplay(z@a)";
+        let coordinator = compile(source);
+        let meta_graph = coordinator.meta_graph().unwrap();
+        let mut debugger = Debugger::new(meta_graph, source);
+        // `y<a> = x@a` is on line 2; `z` still runs after it.
+        debugger.add_breakpoint(None, 2);
+
+        let info = debugger.run_to_breakpoint().expect("should hit the breakpoint");
+        assert_eq!(info.instance_name, "y");
+        assert!(!debugger.is_done());
+    }
+
+    #[test]
+    fn run_to_breakpoint_returns_none_when_nothing_matches() {
+        let source = "x<a> = 1\nplay(x@a)";
+        let coordinator = compile(source);
+        let meta_graph = coordinator.meta_graph().unwrap();
+        let mut debugger = Debugger::new(meta_graph, source);
+        debugger.add_breakpoint(None, 999);
+
+        assert!(debugger.run_to_breakpoint().is_none());
+        assert!(debugger.is_done());
+    }
+
+    #[test]
+    fn matches_breakpoint_requires_both_file_and_line() {
+        let source = "x<a> = 1\nplay(x@a)";
+        let coordinator = compile(source);
+        let meta_graph = coordinator.meta_graph().unwrap();
+        let mut debugger = Debugger::new(meta_graph, source);
+        debugger.add_breakpoint(Some("other.weft".to_string()), 1);
+
+        let span = Span::new(0, 1, None);
+        assert!(
+            !debugger.matches_breakpoint(&span),
+            "a breakpoint scoped to a different file should not match a span with no file"
+        );
+    }
+}
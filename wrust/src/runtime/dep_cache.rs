@@ -0,0 +1,142 @@
+//! On-disk persistence for `RenderGraph`'s incremental-rebuild state,
+//! modeled on rustc's serialized dep-graph: a [`DiskCache`] is the
+//! fingerprint/read-set/context snapshot `RenderGraph::record_snapshot`
+//! already keeps in memory, written out after a successful build and
+//! loaded back in on the next process run so `RenderGraph::build_with_cache`
+//! can seed a `rebuild` as if the previous build had happened in this same
+//! process.
+//!
+//! There's no on-disk format convention elsewhere in this crate, so this
+//! uses a plain line-oriented text format rather than pulling in a new
+//! serialization dependency for one cache file.
+
+use super::backend_registry::Context;
+use crate::utils::Result;
+use crate::WeftError;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+/// Every keyword `backend_registry::get_context` and
+/// `builtin_registry::get_builtin_context` recognize. `phase0_initial_typing`
+/// consults both registries, so a cache built against an older version of
+/// either one must not be trusted -- hashing this list and storing it
+/// alongside the cached nodes gives a cheap version check. Keep it in sync
+/// by hand whenever either registry's match arms change.
+const REGISTRY_KEYWORDS: &[&str] = &[
+    "display",
+    "render",
+    "render_3d",
+    "play",
+    "compute",
+    "data",
+    "web",
+    "osc",
+    "midi",
+    "load_movie",
+    "load_video",
+    "load_image",
+    "camera",
+    "camera_in",
+    "load_audio",
+    "mic_in",
+    "microphone",
+];
+
+pub fn registry_fingerprint() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for keyword in REGISTRY_KEYWORDS {
+        keyword.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// One node's cached state: the same `(fingerprint, reads, context)` triple
+/// `RenderGraph` keeps live in `node_fingerprints`/`node_reads`/the graph
+/// itself, flattened so it survives a round trip through a file.
+#[derive(Debug, Clone)]
+pub struct CachedNode {
+    pub fingerprint: u64,
+    pub reads: HashSet<String>,
+    pub context: Context,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiskCache {
+    pub registry_fingerprint: u64,
+    pub nodes: HashMap<String, CachedNode>,
+}
+
+impl DiskCache {
+    /// Loads a cache from `path`. Any failure to read or parse the file --
+    /// missing, truncated, written by an incompatible version -- is treated
+    /// as an empty cache rather than an error, the same way a missing
+    /// fingerprint is treated as "dirty" rather than fatal: a cold cache
+    /// just costs a full rebuild instead of breaking `build_with_cache`.
+    pub fn load(path: &Path) -> Self {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        Self::parse(&contents).unwrap_or_default()
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let mut lines = contents.lines();
+        let registry_fingerprint = lines
+            .next()?
+            .strip_prefix("registry:")?
+            .parse::<u64>()
+            .ok()?;
+
+        let mut nodes = HashMap::new();
+        for line in lines {
+            let rest = line.strip_prefix("node:")?;
+            let mut fields = rest.splitn(4, '\t');
+            let name = fields.next()?.to_string();
+            let fingerprint = fields.next()?.parse::<u64>().ok()?;
+            let context = match fields.next()? {
+                "Visual" => Context::Visual,
+                "Audio" => Context::Audio,
+                "Compute" => Context::Compute,
+                _ => return None,
+            };
+            let reads = fields
+                .next()
+                .unwrap_or("")
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
+
+            nodes.insert(
+                name,
+                CachedNode {
+                    fingerprint,
+                    reads,
+                    context,
+                },
+            );
+        }
+
+        Some(Self {
+            registry_fingerprint,
+            nodes,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut out = format!("registry:{}\n", self.registry_fingerprint);
+        for (name, cached) in &self.nodes {
+            let reads = cached.reads.iter().cloned().collect::<Vec<_>>().join(",");
+            out.push_str(&format!(
+                "node:{}\t{}\t{}\t{}\n",
+                name,
+                cached.fingerprint,
+                cached.context.name(),
+                reads
+            ));
+        }
+        std::fs::write(path, out).map_err(WeftError::Io)
+    }
+}
@@ -1,5 +1,571 @@
 use crate::ast::SpindleDef;
 use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A source of wall-clock-like time for `Env`. Reading the clock inline
+/// (`std::time::SystemTime::now()`) made timing tests flaky (they had to
+/// `sleep` and tolerate a window) and made it impossible to drive `Env`
+/// faster or slower than realtime. Boxing this lets `Env` swap in a
+/// `ManualClock` for deterministic tests and offline rendering while
+/// `SystemClock` keeps the default, realtime behavior.
+///
+/// `Send + Sync` so `Box<dyn Clock>` (and therefore `Env` as a whole) can
+/// be shared across the worker threads `Coordinator::execute` spawns for
+/// a parallel dependency level.
+pub trait Clock: Send + Sync {
+    fn now_secs(&self) -> f64;
+
+    /// Advances a manual clock by `dt` seconds; a no-op for any clock that
+    /// tracks time on its own (e.g. `SystemClock`).
+    fn advance(&self, _dt: f64) {}
+
+    fn clone_box(&self) -> Box<dyn Clock>;
+}
+
+impl Clone for Box<dyn Clock> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Reads the real wall clock. `Env`'s default, matching its pre-`Clock`
+/// behavior.
+#[derive(Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64()
+    }
+
+    fn clone_box(&self) -> Box<dyn Clock> {
+        Box::new(self.clone())
+    }
+}
+
+/// A clock that only moves when `advance`d. Starts at zero, so an `Env`
+/// built with one and immediately `start()`ed has a playhead of zero
+/// too -- `advance`ing it by an exact `dt` is then an exact, flake-free
+/// stand-in for "sleep and hope the scheduler wakes us up on time".
+///
+/// Guards `now` with a `Mutex` rather than a `Cell` so `ManualClock` (and
+/// therefore `Env`) stays `Sync`; `advance`/`now_secs` only ever hold it
+/// for the duration of one read-or-write, never across a call boundary.
+#[derive(Default)]
+pub struct ManualClock {
+    now: Mutex<f64>,
+}
+
+impl Clone for ManualClock {
+    fn clone(&self) -> Self {
+        Self {
+            now: Mutex::new(*self.now.lock().unwrap()),
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_secs(&self) -> f64 {
+        *self.now.lock().unwrap()
+    }
+
+    fn advance(&self, dt: f64) {
+        *self.now.lock().unwrap() += dt;
+    }
+
+    fn clone_box(&self) -> Box<dyn Clock> {
+        Box::new(self.clone())
+    }
+}
+
+/// Ticks per `Superseconds` second -- divisible by every sample rate this
+/// crate supports (44100, 48000, 88200, 96000, ...), so converting to a
+/// sample count is an exact integer division rather than an `f64`
+/// multiply that can drift or land off a sample boundary.
+pub const SUPERSECOND_TICKS_PER_SEC: u64 = 282_240_000;
+
+/// Ticks per `Superbeats` beat -- divisible by the subdivisions music
+/// actually uses (halves, thirds, quarters, fifths, triplets), so a beat
+/// position can land exactly on one instead of drifting toward it.
+pub const SUPERBEAT_TICKS_PER_BEAT: u64 = 705_600_000;
+
+/// Fixed-point time as a count of `1/SUPERSECOND_TICKS_PER_SEC`-second
+/// ticks. Exists so sample positions derived from elapsed time are exact
+/// integer math instead of `f64` seconds accumulating rounding error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Superseconds(pub u64);
+
+impl Superseconds {
+    pub fn from_seconds(secs: f64) -> Self {
+        Self((secs * SUPERSECOND_TICKS_PER_SEC as f64).round() as u64)
+    }
+
+    pub fn to_seconds(self) -> f64 {
+        self.0 as f64 / SUPERSECOND_TICKS_PER_SEC as f64
+    }
+
+    /// Converts `beats` at `tempo` (beats per minute) to elapsed time.
+    pub fn from_beats(beats: Superbeats, tempo: f64) -> Self {
+        Self::from_seconds(beats.to_beats() * 60.0 / tempo)
+    }
+
+    /// The exact sample index `sample_rate` ticks per second land this
+    /// instant on. `SUPERSECOND_TICKS_PER_SEC` being divisible by every
+    /// supported rate means this multiply-then-divide never rounds.
+    pub fn to_samples(self, sample_rate: u64) -> u64 {
+        self.0 * sample_rate / SUPERSECOND_TICKS_PER_SEC
+    }
+}
+
+impl std::ops::Add for Superseconds {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Superseconds {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Mul<u64> for Superseconds {
+    type Output = Self;
+    fn mul(self, rhs: u64) -> Self {
+        Self(self.0 * rhs)
+    }
+}
+
+/// Fixed-point beat position as a count of `1/SUPERBEAT_TICKS_PER_BEAT`-
+/// beat ticks, the beat-domain counterpart to `Superseconds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Superbeats(pub u64);
+
+impl Superbeats {
+    pub fn from_beats(beats: f64) -> Self {
+        Self((beats * SUPERBEAT_TICKS_PER_BEAT as f64).round() as u64)
+    }
+
+    pub fn to_beats(self) -> f64 {
+        self.0 as f64 / SUPERBEAT_TICKS_PER_BEAT as f64
+    }
+
+    /// Converts an elapsed duration to a beat position at `tempo` (beats
+    /// per minute).
+    pub fn from_seconds(secs: Superseconds, tempo: f64) -> Self {
+        Self::from_beats(secs.to_seconds() / 60.0 * tempo)
+    }
+}
+
+impl std::ops::Add for Superbeats {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Superbeats {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Mul<u64> for Superbeats {
+    type Output = Self;
+    fn mul(self, rhs: u64) -> Self {
+        Self(self.0 * rhs)
+    }
+}
+
+/// One tempo/time-signature change, effective from `start_time` (in
+/// `Env::time()`'s looped seconds) until the next point's `start_time`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingPoint {
+    pub start_time: f64,
+    pub bpm: f64,
+    pub timesig_num: u32,
+    pub timesig_denom: u32,
+}
+
+/// A sorted sequence of `TimingPoint`s with a precomputed cumulative beat
+/// count at the start of each one, so `current_beat`/`time_at_beat` don't
+/// have to re-walk every earlier point on every call. A single-point map
+/// (the common case) behaves exactly like the flat `tempo`/`timesig_*`
+/// fields `Env` used to hardcode.
+#[derive(Debug, Clone, Default)]
+pub struct TempoMap {
+    points: Vec<TimingPoint>,
+    beats_before: Vec<f64>,
+}
+
+impl TempoMap {
+    /// Inserts `point`, keeping `points` sorted by `start_time`, and
+    /// recomputes `beats_before` for the whole map -- a tempo change
+    /// anywhere shifts the cumulative beat count of every later point.
+    pub fn insert_point(&mut self, point: TimingPoint) {
+        let idx = self
+            .points
+            .partition_point(|p| p.start_time <= point.start_time);
+        self.points.insert(idx, point);
+        self.recompute_beats_before();
+    }
+
+    fn recompute_beats_before(&mut self) {
+        self.beats_before.clear();
+        let mut beats = 0.0;
+        let mut prev_start = 0.0;
+        let mut prev_bpm = self.points.first().map_or(120.0, |p| p.bpm);
+        for point in &self.points {
+            beats += (point.start_time - prev_start) / 60.0 * prev_bpm;
+            self.beats_before.push(beats);
+            prev_start = point.start_time;
+            prev_bpm = point.bpm;
+        }
+    }
+
+    /// Index of the last point with `start_time <= t`, or `0` if `t` is
+    /// before every point (or the map is empty).
+    fn point_index_at(&self, t: f64) -> usize {
+        match self.points.partition_point(|p| p.start_time <= t) {
+            0 => 0,
+            n => n - 1,
+        }
+    }
+
+    pub fn active_point(&self, t: f64) -> Option<&TimingPoint> {
+        self.points.get(self.point_index_at(t))
+    }
+
+    /// The beat position at time `t`: binary-searches for the last point
+    /// with `start_time <= t`, then adds that point's own elapsed beats
+    /// to its precomputed `beats_before`.
+    pub fn current_beat(&self, t: f64) -> f64 {
+        let Some(idx) = (!self.points.is_empty()).then(|| self.point_index_at(t)) else {
+            return 0.0;
+        };
+        let point = &self.points[idx];
+        self.beats_before[idx] + (t - point.start_time) / 60.0 * point.bpm
+    }
+
+    /// The inverse of `current_beat`: binary-searches `beats_before` for
+    /// the last point reached by `beat`, then interpolates back to
+    /// seconds using that point's own bpm.
+    pub fn time_at_beat(&self, beat: f64) -> f64 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        let idx = match self.beats_before.partition_point(|&b| b <= beat) {
+            0 => 0,
+            n => n - 1,
+        };
+        let point = &self.points[idx];
+        point.start_time + (beat - self.beats_before[idx]) / point.bpm * 60.0
+    }
+}
+
+/// How a transport's playhead folds back into `[0, loop_duration]` once
+/// it runs past the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Stop advancing once `loop_duration` is reached.
+    Once,
+    /// Wrap back to `0`, same as the old `abstime() % loop_duration`.
+    Loop,
+    /// Bounce back and forth between `0` and `loop_duration`, reversing
+    /// direction at each endpoint.
+    PingPong,
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Loop
+    }
+}
+
+/// Playback position, decoupled from wall-clock time so a controlling UI
+/// or OSC/MIDI compute backend (see `get_context`) can pause, scrub, or
+/// change playback rate without `Env` ever computing position as a plain
+/// `now - start_time` delta.
+///
+/// Mirrors the anchor-and-rate clock a media player keeps: the playhead
+/// is `anchor_playhead_secs + (now_wall - anchor_wall_secs) * rate` while
+/// running, frozen at `anchor_playhead_secs` while paused. Re-anchoring
+/// on every `pause`/`resume`/`seek`/`set_rate` means reading the playhead
+/// is a pure function of the current wall time -- it never needs `&mut
+/// self`, the same way `Env::abstime` used to be a pure function of
+/// `start_time`.
+#[derive(Debug, Clone)]
+pub struct Transport {
+    pub rate: f64,
+    pub paused: bool,
+    pub loop_mode: LoopMode,
+    anchor_wall_secs: f64,
+    anchor_playhead_secs: f64,
+}
+
+impl Transport {
+    pub fn new() -> Self {
+        Self {
+            rate: 1.0,
+            paused: false,
+            loop_mode: LoopMode::default(),
+            anchor_wall_secs: 0.0,
+            anchor_playhead_secs: 0.0,
+        }
+    }
+
+    /// Re-anchors at `now_wall` without moving the playhead -- called
+    /// before any change that would otherwise make the next read jump.
+    fn reanchor(&mut self, now_wall: f64) {
+        self.anchor_playhead_secs = self.playhead_secs(now_wall);
+        self.anchor_wall_secs = now_wall;
+    }
+
+    pub fn pause(&mut self, now_wall: f64) {
+        self.reanchor(now_wall);
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self, now_wall: f64) {
+        self.reanchor(now_wall);
+        self.paused = false;
+    }
+
+    pub fn seek(&mut self, now_wall: f64, secs: f64) {
+        self.anchor_wall_secs = now_wall;
+        self.anchor_playhead_secs = secs;
+    }
+
+    pub fn set_rate(&mut self, now_wall: f64, rate: f64) {
+        self.reanchor(now_wall);
+        self.rate = rate;
+    }
+
+    /// The playhead's absolute (unlooped) position at `now_wall`.
+    pub fn playhead_secs(&self, now_wall: f64) -> f64 {
+        if self.paused {
+            self.anchor_playhead_secs
+        } else {
+            self.anchor_playhead_secs + (now_wall - self.anchor_wall_secs) * self.rate
+        }
+    }
+
+    /// `playhead_secs` folded into `[0, loop_duration]` per `loop_mode` --
+    /// `PingPong` reflects back and forth instead of wrapping, so the
+    /// result (and anything derived from it, like `frame`/`sample`)
+    /// reverses at each endpoint.
+    pub fn looped_secs(&self, now_wall: f64, loop_duration: f64) -> f64 {
+        if loop_duration <= 0.0 {
+            return 0.0;
+        }
+        let t = self.playhead_secs(now_wall);
+        match self.loop_mode {
+            LoopMode::Once => t.clamp(0.0, loop_duration),
+            LoopMode::Loop => t.rem_euclid(loop_duration),
+            LoopMode::PingPong => {
+                let period = loop_duration * 2.0;
+                let pos = t.rem_euclid(period);
+                if pos <= loop_duration {
+                    pos
+                } else {
+                    period - pos
+                }
+            }
+        }
+    }
+
+    /// The `Superseconds`-tick analogue of `looped_secs`, so the looped
+    /// sample count can be derived with the same exact integer math
+    /// `abssample` already uses instead of folding a rounded `f64`.
+    fn fold_ticks(&self, abs_ticks: u64, loop_ticks: u64) -> u64 {
+        if loop_ticks == 0 {
+            return 0;
+        }
+        match self.loop_mode {
+            LoopMode::Once => abs_ticks.min(loop_ticks),
+            LoopMode::Loop => abs_ticks % loop_ticks,
+            LoopMode::PingPong => {
+                let period_ticks = loop_ticks * 2;
+                let pos = abs_ticks % period_ticks;
+                if pos <= loop_ticks {
+                    pos
+                } else {
+                    period_ticks - pos
+                }
+            }
+        }
+    }
+}
+
+/// A typed value produced by converting a CLI-supplied `--set KEY=VALUE`
+/// string through a [`Conversion`], and stashed in [`Env::overrides`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvValue {
+    Bytes(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    /// A timestamp matched against a `strptime`-style format string (see
+    /// `parse_timestamp`), stored as its normalized `YYYY-MM-DDTHH:MM:SS`
+    /// rendering -- the program sees a plain string either way, this just
+    /// validates it parses as a timestamp first.
+    Timestamp(String),
+}
+
+/// Names one of the string-to-[`EnvValue`] conversions `--set` can apply to
+/// an override, parsed from the `:type` suffix on a `--set KEY=VALUE:type`
+/// flag (`Conversion::Bytes` when the suffix is omitted).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// Taken as-is, with no conversion. The default when no `:type` suffix
+    /// is given.
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    /// Parsed with the given `strptime`-style format string, e.g.
+    /// `timestamp(%Y-%m-%d)`.
+    TimestampFmt(String),
+}
+
+impl std::str::FromStr for Conversion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(fmt) = s.strip_prefix("timestamp(").and_then(|s| s.strip_suffix(')')) {
+            return Ok(Conversion::TimestampFmt(fmt.to_string()));
+        }
+        match s {
+            "bytes" => Ok(Conversion::Bytes),
+            "int" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" => Ok(Conversion::Boolean),
+            other => Err(format!(
+                "unknown conversion `{}` (expected `int`, `float`, `bool`, `bytes`, or `timestamp(FMT)`)",
+                other
+            )),
+        }
+    }
+}
+
+impl Conversion {
+    /// Applies this conversion to `raw`, producing the typed value an
+    /// override should carry. The error names neither the key nor the
+    /// flag that failed -- callers (e.g. `weft run --set`) are expected to
+    /// wrap it with that context.
+    pub fn convert(&self, raw: &str) -> Result<EnvValue, String> {
+        match self {
+            Conversion::Bytes => Ok(EnvValue::Bytes(raw.to_string())),
+            Conversion::Integer => raw
+                .parse::<i64>()
+                .map(EnvValue::Integer)
+                .map_err(|e| format!("not a valid integer: {}", e)),
+            Conversion::Float => raw
+                .parse::<f64>()
+                .map(EnvValue::Float)
+                .map_err(|e| format!("not a valid float: {}", e)),
+            Conversion::Boolean => raw
+                .parse::<bool>()
+                .map(EnvValue::Boolean)
+                .map_err(|e| format!("not a valid boolean: {}", e)),
+            Conversion::TimestampFmt(fmt) => parse_timestamp(raw, fmt)
+                .map(EnvValue::Timestamp)
+                .map_err(|e| format!("does not match timestamp format `{}`: {}", fmt, e)),
+        }
+    }
+}
+
+/// Matches `raw` against a `strptime`-style format string and, on success,
+/// returns it normalized to `YYYY-MM-DDTHH:MM:SS`. Supports the handful of
+/// directives a `--set key=val:timestamp(FMT)` override plausibly needs --
+/// `%Y` (4-digit year), `%m`/`%d`/`%H`/`%M`/`%S` (2-digit month, day, hour,
+/// minute, second) -- plus literal text matched verbatim; anything else in
+/// `fmt` is an error, not a silent pass-through.
+fn parse_timestamp(raw: &str, fmt: &str) -> Result<String, String> {
+    let mut year = None;
+    let mut month = 1u32;
+    let mut day = 1u32;
+    let mut hour = 0u32;
+    let mut minute = 0u32;
+    let mut second = 0u32;
+
+    let mut fmt_chars = fmt.chars().peekable();
+    let mut raw_chars = raw.chars().peekable();
+
+    fn take_digits(raw_chars: &mut std::iter::Peekable<std::str::Chars<'_>>, n: usize) -> Result<u32, String> {
+        let mut digits = String::new();
+        for _ in 0..n {
+            match raw_chars.peek() {
+                Some(c) if c.is_ascii_digit() => {
+                    digits.push(*c);
+                    raw_chars.next();
+                }
+                _ => break,
+            }
+        }
+        if digits.is_empty() {
+            return Err("expected a number".to_string());
+        }
+        digits.parse::<u32>().map_err(|e| e.to_string())
+    }
+
+    while let Some(&fc) = fmt_chars.peek() {
+        if fc == '%' {
+            fmt_chars.next();
+            let directive = fmt_chars
+                .next()
+                .ok_or_else(|| "dangling `%` in format".to_string())?;
+            match directive {
+                'Y' => year = Some(take_digits(&mut raw_chars, 4)?),
+                'm' => month = take_digits(&mut raw_chars, 2)?,
+                'd' => day = take_digits(&mut raw_chars, 2)?,
+                'H' => hour = take_digits(&mut raw_chars, 2)?,
+                'M' => minute = take_digits(&mut raw_chars, 2)?,
+                'S' => second = take_digits(&mut raw_chars, 2)?,
+                other => return Err(format!("unsupported format directive `%{}`", other)),
+            }
+        } else {
+            match raw_chars.next() {
+                Some(rc) if rc == fc => {}
+                Some(rc) => return Err(format!("expected `{}`, found `{}`", fc, rc)),
+                None => return Err(format!("expected `{}`, found end of input", fc)),
+            }
+            fmt_chars.next();
+        }
+    }
+
+    if raw_chars.peek().is_some() {
+        return Err("trailing characters after format match".to_string());
+    }
+
+    let year = year.ok_or_else(|| "format has no `%Y` year directive".to_string())?;
+    if !(1..=12).contains(&month) {
+        return Err(format!("month {} out of range", month));
+    }
+    if !(1..=31).contains(&day) {
+        return Err(format!("day {} out of range", day));
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err("time-of-day component out of range".to_string());
+    }
+
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    ))
+}
 
 #[derive(Clone)]
 pub struct Env {
@@ -10,9 +576,10 @@ pub struct Env {
     // program timing
     pub frame: u64,
     pub absframe: u64,
-    pub start_time: f64, //epoch seconds
     pub target_fps: f64,
     pub loop_duration: f64,
+    clock: Box<dyn Clock>,
+    transport: Transport,
 
     // user
     pub spindles: HashMap<String, SpindleDef>,
@@ -24,20 +591,39 @@ pub struct Env {
     pub tempo: f64,
     pub timesig_num: u32,
     pub timesig_denom: u32,
+    /// Timing points beyond the flat `tempo`/`timesig_*` fields above --
+    /// empty in the common single-tempo case. `tempo_map_effective`
+    /// layers the flat fields on top of this as the point at
+    /// `start_time: 0.0`, so mutating `tempo` directly keeps working
+    /// exactly as it always did.
+    tempo_map: TempoMap,
     // media
     //pub media: HashMap<StriNng, Sampler>,
+    /// Externally-supplied overrides for top-level environment assignments
+    /// (e.g. `weft run --set seed=42:int`), keyed by the assignment's
+    /// name. Consulted nowhere yet by the runtime itself -- the CLI is the
+    /// only current producer -- but a future interpreter reads this before
+    /// falling back to the program's own `me<name> = ...` assignment.
+    pub overrides: HashMap<String, EnvValue>,
 }
 
 impl Env {
     pub fn new(width: u32, height: u32) -> Self {
+        Self::with_clock(width, height, Box::new(SystemClock))
+    }
+
+    /// Like `new`, but driven by `clock` instead of the real wall clock --
+    /// pass a `ManualClock` for deterministic tests or offline rendering.
+    pub fn with_clock(width: u32, height: u32, clock: Box<dyn Clock>) -> Self {
         Self {
             res_w: width,
             res_h: height,
             frame: 0,
             absframe: 0,
-            start_time: 0.0,
             target_fps: 60.0,
             loop_duration: 10.0,
+            clock,
+            transport: Transport::new(),
             spindles: HashMap::new(),
             sample_rate: 48000.0,
             sample: 0,
@@ -45,34 +631,127 @@ impl Env {
             tempo: 120.0,
             timesig_num: 4,
             timesig_denom: 4,
+            tempo_map: TempoMap::default(),
+            overrides: HashMap::new(),
         }
     }
 
+    /// Records `value` as an override for the top-level environment
+    /// assignment named `key`, replacing any prior override of the same
+    /// name.
+    pub fn set_override(&mut self, key: impl Into<String>, value: EnvValue) {
+        self.overrides.insert(key.into(), value);
+    }
+
+    /// Adds a tempo/time-signature change effective from `point.start_time`
+    /// (in `time()`'s looped seconds) onward, for programs whose tempo
+    /// changes along the timeline rather than staying fixed.
+    pub fn insert_timing_point(&mut self, point: TimingPoint) {
+        self.tempo_map.insert_point(point);
+    }
+
+    /// `tempo_map` with the flat `tempo`/`timesig_*` fields folded in as
+    /// its `start_time: 0.0` point, so `current_beat` et al. only ever
+    /// need to consult one map.
+    fn tempo_map_effective(&self) -> TempoMap {
+        let mut map = self.tempo_map.clone();
+        map.insert_point(TimingPoint {
+            start_time: 0.0,
+            bpm: self.tempo,
+            timesig_num: self.timesig_num,
+            timesig_denom: self.timesig_denom,
+        });
+        map
+    }
+
+    /// Resets the transport's playhead to `0`, anchored at the current
+    /// wall-clock time.
     pub fn start(&mut self) {
-        self.start_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
+        let now = self.clock.now_secs();
+        self.transport.seek(now, 0.0);
+    }
+
+    /// Advances a manual clock by `dt` seconds; a no-op under `SystemClock`.
+    pub fn advance(&mut self, dt: f64) {
+        self.clock.advance(dt);
+    }
+
+    /// Freezes the transport's playhead at its current position.
+    pub fn pause(&mut self) {
+        let now = self.clock.now_secs();
+        self.transport.pause(now);
+    }
+
+    /// Resumes advancing the transport's playhead from wherever it was
+    /// frozen.
+    pub fn resume(&mut self) {
+        let now = self.clock.now_secs();
+        self.transport.resume(now);
     }
 
+    /// Jumps the transport's playhead to `secs` (in unlooped, absolute
+    /// playback time -- the same domain as `abstime`).
+    pub fn seek(&mut self, secs: f64) {
+        let now = self.clock.now_secs();
+        self.transport.seek(now, secs);
+    }
+
+    /// Sets the transport's playback speed multiplier (negative plays
+    /// backward).
+    pub fn set_rate(&mut self, rate: f64) {
+        let now = self.clock.now_secs();
+        self.transport.set_rate(now, rate);
+    }
+
+    /// Sets how the transport's playhead folds back into `[0,
+    /// loop_duration]` once it runs past the end.
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        self.transport.loop_mode = mode;
+    }
+
+    /// Drives `frames` fixed-timestep steps entirely off this `Env`'s
+    /// clock: advances it by `dt`, resyncs `frame`/`sample`/etc. via
+    /// `sync_counters`, then hands the updated `Env` to `step`. Lets a
+    /// headless backend render frame-accurate output faster or slower
+    /// than realtime without the wall clock entering the loop -- pair
+    /// with a `ManualClock`, since `advance` is a no-op otherwise.
+    pub fn render_offline(&mut self, frames: u64, dt: f64, mut step: impl FnMut(&Env)) {
+        for _ in 0..frames {
+            self.advance(dt);
+            self.sync_counters();
+            step(self);
+        }
+    }
+
+    /// The transport's absolute (unlooped) playback position.
     pub fn abstime(&self) -> f64 {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
-        now - self.start_time
+        self.transport.playhead_secs(self.clock.now_secs())
     }
 
+    /// The transport's playback position folded into `[0, loop_duration]`
+    /// per its `LoopMode`.
     pub fn time(&self) -> f64 {
-        self.abstime() % self.loop_duration
+        self.transport
+            .looped_secs(self.clock.now_secs(), self.loop_duration)
     }
 
     pub fn current_beat(&self) -> f64 {
-        (self.time() / 60.0) * self.tempo
+        self.tempo_map_effective().current_beat(self.time())
+    }
+
+    /// The inverse of `current_beat`: the (looped) time `beat` falls at,
+    /// given the same tempo map `current_beat` reads.
+    pub fn time_at_beat(&self, beat: f64) -> f64 {
+        self.tempo_map_effective().time_at_beat(beat)
     }
 
     pub fn current_measure(&self) -> f64 {
-        self.current_beat() / self.timesig_num as f64
+        let t = self.time();
+        let map = self.tempo_map_effective();
+        let timesig_num = map
+            .active_point(t)
+            .map_or(self.timesig_num, |p| p.timesig_num);
+        map.current_beat(t) / timesig_num as f64
     }
 
     pub fn beat_phase(&self) -> f64 {
@@ -87,16 +766,26 @@ impl Env {
         self.absframe = (abs_time * self.target_fps) as u64;
         self.frame = (self.time() * self.target_fps) as u64;
 
-        self.abssample = (abs_time * self.sample_rate) as u64;
-        self.sample = (self.time() * self.sample_rate) as u64;
+        // Sample counters go through Superseconds instead of `abs_time *
+        // sample_rate` directly: `fold_ticks` below is exact integer math
+        // mirroring `Transport::looped_secs`, so `sample` never drifts by
+        // a tick relative to `abssample` regardless of loop mode.
+        let abs_ticks = Superseconds::from_seconds(abs_time);
+        let loop_ticks = Superseconds::from_seconds(self.loop_duration).0;
+        let ticks_in_loop = self.transport.fold_ticks(abs_ticks.0, loop_ticks);
+
+        self.abssample = abs_ticks.to_samples(self.sample_rate as u64);
+        self.sample = Superseconds(ticks_in_loop).to_samples(self.sample_rate as u64);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread::sleep;
-    use std::time::Duration;
+
+    fn manual_env(width: u32, height: u32) -> Env {
+        Env::with_clock(width, height, Box::new(ManualClock::new()))
+    }
 
     #[test]
     fn test_env_new() {
@@ -105,147 +794,344 @@ mod tests {
         assert_eq!(env.res_h, 1080);
         assert_eq!(env.frame, 0);
         assert_eq!(env.absframe, 0);
-        assert_eq!(env.start_time, 0.0);
         assert_eq!(env.target_fps, 60.0);
         assert_eq!(env.sample_rate, 48000.0);
     }
 
     #[test]
-    fn test_start_sets_time() {
+    fn test_start_resets_playhead_to_zero() {
         let mut env = Env::new(800, 600);
-        assert_eq!(env.start_time, 0.0);
-
         env.start();
-        assert!(env.start_time > 0.0);
+        assert_eq!(env.abstime(), 0.0);
     }
 
     #[test]
     fn test_abstime_tracks_elapsed() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.start();
+        assert_eq!(env.abstime(), 0.0);
 
-        let t0 = env.abstime();
-        assert!(t0 >= 0.0 && t0 < 0.1); // Should be near zero
-
-        sleep(Duration::from_millis(100));
-
-        let t1 = env.abstime();
-        assert!(t1 >= 0.09 && t1 <= 0.15); // ~100ms elapsed
+        env.advance(0.1);
+        assert_eq!(env.abstime(), 0.1);
     }
 
     #[test]
     fn test_time_wraps_based_on_loop_duration() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.loop_duration = 1.0; // 1 second loop
         env.start();
 
-        // Manually set start_time to simulate time passage
-        env.start_time -= 2.5; // Simulate 2.5 seconds ago
+        env.advance(2.5); // 2.5 loops
 
-        let time = env.time();
-        assert!(time >= 0.4 && time <= 0.6); // Should be around 0.5 (2.5 % 1.0)
+        assert_eq!(env.time(), 0.5);
     }
 
     #[test]
     fn test_sync_counters_updates_frames() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.target_fps = 60.0;
         env.start();
 
-        // Simulate 1 second elapsed
-        env.start_time -= 1.0;
-
+        env.advance(1.0);
         env.sync_counters();
 
-        // After 1 second at 60fps, should be ~60 frames
-        assert!(env.absframe >= 59 && env.absframe <= 61);
+        assert_eq!(env.absframe, 60);
     }
 
     #[test]
     fn test_sync_counters_updates_samples() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.sample_rate = 48000.0;
         env.start();
 
-        // Simulate 1 second elapsed
-        env.start_time -= 1.0;
-
+        env.advance(1.0);
         env.sync_counters();
 
-        // After 1 second at 48kHz, should be ~48000 samples
-        assert!(env.abssample >= 47900 && env.abssample <= 48100);
+        assert_eq!(env.abssample, 48000);
     }
 
     #[test]
     fn test_sync_counters_wraps_looping_counters() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.loop_duration = 1.0; // 1 second loop
         env.target_fps = 60.0;
         env.start();
 
-        // Simulate 2.5 seconds elapsed (2.5 loops)
-        env.start_time -= 2.5;
-
+        env.advance(2.5); // 2.5 loops
         env.sync_counters();
 
-        // Looping frame should be around 30 (0.5s * 60fps)
-        assert!(env.frame >= 29 && env.frame <= 31);
-
-        // Absolute frame should be around 150 (2.5s * 60fps)
-        assert!(env.absframe >= 149 && env.absframe <= 151);
+        assert_eq!(env.frame, 30); // 0.5s * 60fps
+        assert_eq!(env.absframe, 150); // 2.5s * 60fps
     }
 
     #[test]
     fn test_current_beat() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.tempo = 120.0; // 120 BPM = 2 beats per second
         env.start();
 
-        // Simulate 1 second elapsed
-        env.start_time -= 1.0;
+        env.advance(1.0);
 
-        let beat = env.current_beat();
-        assert!(beat >= 1.9 && beat <= 2.1); // Should be ~2 beats
+        assert_eq!(env.current_beat(), 2.0);
     }
 
     #[test]
     fn test_current_measure() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.tempo = 120.0; // 2 beats/sec
         env.timesig_num = 4; // 4/4 time
         env.start();
 
-        // Simulate 2 seconds elapsed = 4 beats = 1 measure
-        env.start_time -= 2.0;
+        env.advance(2.0); // 4 beats = 1 measure
 
-        let measure = env.current_measure();
-        assert!(measure >= 0.95 && measure <= 1.05); // Should be ~1 measure
+        assert_eq!(env.current_measure(), 1.0);
     }
 
     #[test]
     fn test_beat_phase() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.tempo = 60.0; // 1 beat per second
         env.start();
 
-        // Simulate 1.25 seconds elapsed = 1.25 beats
-        env.start_time -= 1.25;
+        env.advance(1.25); // 1.25 beats
 
-        let phase = env.beat_phase();
-        assert!(phase >= 0.24 && phase <= 0.26); // Should be 0.25 (fractional part)
+        assert_eq!(env.beat_phase(), 0.25);
     }
 
     #[test]
     fn test_measure_phase() {
-        let mut env = Env::new(800, 600);
+        let mut env = manual_env(800, 600);
         env.tempo = 120.0; // 2 beats/sec
         env.timesig_num = 4; // 4/4
         env.start();
 
-        // Simulate 3 seconds = 6 beats = 1.5 measures
-        env.start_time -= 3.0;
+        env.advance(3.0); // 6 beats = 1.5 measures
+
+        assert_eq!(env.measure_phase(), 0.5);
+    }
+
+    #[test]
+    fn test_render_offline_steps_fixed_dt_without_wall_clock() {
+        let mut env = manual_env(800, 600);
+        env.target_fps = 60.0;
+        env.loop_duration = 10.0;
+        env.start();
+
+        let mut seen_frames = Vec::new();
+        env.render_offline(3, 1.0 / 60.0, |e| seen_frames.push(e.absframe));
+
+        assert_eq!(seen_frames, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_pause_freezes_abstime() {
+        let mut env = manual_env(800, 600);
+        env.start();
+
+        env.advance(1.0);
+        env.pause();
+        env.advance(1.0); // should not move the playhead while paused
+
+        assert_eq!(env.abstime(), 1.0);
+    }
+
+    #[test]
+    fn test_resume_continues_from_paused_position() {
+        let mut env = manual_env(800, 600);
+        env.start();
+
+        env.advance(1.0);
+        env.pause();
+        env.advance(1.0);
+        env.resume();
+        env.advance(0.5);
+
+        assert_eq!(env.abstime(), 1.5);
+    }
+
+    #[test]
+    fn test_seek_jumps_the_playhead() {
+        let mut env = manual_env(800, 600);
+        env.start();
+
+        env.advance(1.0);
+        env.seek(5.0);
+
+        assert_eq!(env.abstime(), 5.0);
+
+        env.advance(0.5);
+        assert_eq!(env.abstime(), 5.5);
+    }
+
+    #[test]
+    fn test_set_rate_scales_playback_speed() {
+        let mut env = manual_env(800, 600);
+        env.start();
+        env.set_rate(2.0);
+
+        env.advance(1.0);
+
+        assert_eq!(env.abstime(), 2.0);
+    }
+
+    #[test]
+    fn test_set_rate_negative_plays_backward() {
+        let mut env = manual_env(800, 600);
+        env.start();
+        env.seek(5.0);
+        env.set_rate(-1.0);
+
+        env.advance(2.0);
+
+        assert_eq!(env.abstime(), 3.0);
+    }
+
+    #[test]
+    fn test_loop_mode_once_clamps_at_loop_duration() {
+        let mut env = manual_env(800, 600);
+        env.loop_duration = 1.0;
+        env.set_loop_mode(LoopMode::Once);
+        env.start();
+
+        env.advance(2.5);
+
+        assert_eq!(env.time(), 1.0);
+    }
+
+    #[test]
+    fn test_loop_mode_ping_pong_reverses_at_endpoints() {
+        let mut env = manual_env(800, 600);
+        env.loop_duration = 1.0;
+        env.set_loop_mode(LoopMode::PingPong);
+        env.start();
+
+        env.advance(0.5);
+        assert_eq!(env.time(), 0.5); // forward leg
+
+        env.advance(0.75); // t=1.25 -- 0.25s into the reflected leg
+        assert_eq!(env.time(), 0.75);
+
+        env.advance(1.0); // t=2.25 -- 0.25s into the next forward leg
+        assert_eq!(env.time(), 0.25);
+    }
+
+    #[test]
+    fn test_sync_counters_ping_pong_reverses_frame_and_sample() {
+        let mut env = manual_env(800, 600);
+        env.loop_duration = 1.0;
+        env.target_fps = 60.0;
+        env.sample_rate = 48000.0;
+        env.set_loop_mode(LoopMode::PingPong);
+        env.start();
+
+        env.advance(1.25); // 0.25s into the reflected (reverse) leg
+        env.sync_counters();
+
+        assert_eq!(env.frame, 45); // 0.75s * 60fps
+        assert_eq!(env.sample, 36000); // 0.75s * 48000
+        assert_eq!(env.absframe, 75); // unlooped: 1.25s * 60fps
+    }
+
+    #[test]
+    fn test_superseconds_round_trips_seconds() {
+        let ticks = Superseconds::from_seconds(1.5);
+        assert_eq!(ticks.0, SUPERSECOND_TICKS_PER_SEC * 3 / 2);
+        assert_eq!(ticks.to_seconds(), 1.5);
+    }
+
+    #[test]
+    fn test_superseconds_to_samples_is_exact_for_common_rates() {
+        let one_sec = Superseconds::from_seconds(1.0);
+        assert_eq!(one_sec.to_samples(44100), 44100);
+        assert_eq!(one_sec.to_samples(48000), 48000);
+        assert_eq!(one_sec.to_samples(96000), 96000);
+    }
+
+    #[test]
+    fn test_superseconds_arithmetic() {
+        let a = Superseconds::from_seconds(1.0);
+        let b = Superseconds::from_seconds(0.5);
+        assert_eq!((a + b).to_seconds(), 1.5);
+        assert_eq!((a - b).to_seconds(), 0.5);
+        assert_eq!((b * 3).to_seconds(), 1.5);
+    }
+
+    #[test]
+    fn test_superbeats_round_trips_beats() {
+        let ticks = Superbeats::from_beats(2.25);
+        assert_eq!(ticks.0, SUPERBEAT_TICKS_PER_BEAT * 9 / 4);
+        assert_eq!(ticks.to_beats(), 2.25);
+    }
+
+    #[test]
+    fn test_superbeats_from_seconds_uses_tempo() {
+        // 120 BPM = 2 beats/sec, so 1.5 seconds is exactly 3 beats.
+        let beats = Superbeats::from_seconds(Superseconds::from_seconds(1.5), 120.0);
+        assert_eq!(beats.to_beats(), 3.0);
+    }
+
+    #[test]
+    fn test_superseconds_from_beats_uses_tempo() {
+        // 120 BPM = 2 beats/sec, so 3 beats is exactly 1.5 seconds.
+        let secs = Superseconds::from_beats(Superbeats::from_beats(3.0), 120.0);
+        assert_eq!(secs.to_seconds(), 1.5);
+    }
+
+    #[test]
+    fn test_tempo_map_single_point_matches_flat_tempo() {
+        let mut map = TempoMap::default();
+        map.insert_point(TimingPoint {
+            start_time: 0.0,
+            bpm: 120.0,
+            timesig_num: 4,
+            timesig_denom: 4,
+        });
+
+        // 120 BPM = 2 beats/sec.
+        assert_eq!(map.current_beat(1.0), 2.0);
+        assert_eq!(map.time_at_beat(2.0), 1.0);
+    }
+
+    #[test]
+    fn test_tempo_map_honors_change_partway_through() {
+        let mut map = TempoMap::default();
+        map.insert_point(TimingPoint {
+            start_time: 0.0,
+            bpm: 60.0, // 1 beat/sec for the first 2 seconds = 2 beats
+            timesig_num: 4,
+            timesig_denom: 4,
+        });
+        map.insert_point(TimingPoint {
+            start_time: 2.0,
+            bpm: 120.0, // then 2 beats/sec
+            timesig_num: 3,
+            timesig_denom: 4,
+        });
+
+        assert_eq!(map.current_beat(2.0), 2.0);
+        assert_eq!(map.current_beat(3.0), 4.0); // 2 + 1.0 * 2 beats/sec
+        assert_eq!(map.active_point(2.5).unwrap().timesig_num, 3);
+        assert_eq!(map.active_point(1.0).unwrap().timesig_num, 4);
+        assert_eq!(map.time_at_beat(4.0), 3.0);
+    }
+
+    #[test]
+    fn test_env_insert_timing_point_changes_measure_timesig() {
+        let mut env = manual_env(800, 600);
+        env.tempo = 120.0; // 2 beats/sec
+        env.timesig_num = 4;
+        env.start();
+        env.insert_timing_point(TimingPoint {
+            start_time: 1.0,
+            bpm: 120.0,
+            timesig_num: 3,
+            timesig_denom: 4,
+        });
+
+        env.advance(0.5); // before the new point -- still 4/4: 1 beat / 4
+        assert_eq!(env.current_measure(), 0.25);
 
-        let phase = env.measure_phase();
-        assert!(phase >= 0.49 && phase <= 0.51); // Should be 0.5 (fractional part)
+        env.advance(1.0); // t=1.5, past start_time: 1.0 -- now 3/4: 3 beats / 3
+        assert_eq!(env.current_measure(), 1.0);
     }
 }
@@ -2,13 +2,17 @@ pub mod backend_registry;
 pub mod builtin_registry;
 pub mod builtins;
 pub mod coordinator;
+pub mod debugger;
+pub mod dep_cache;
 pub mod env;
 pub mod render_graph;
 pub mod sampler;
+pub mod scheduler;
 pub mod spindle;
 
 #[cfg(test)]
 mod coordinator_test;
 
 pub use coordinator::Coordinator;
+pub use debugger::Debugger;
 pub use env::Env;
@@ -1,19 +1,56 @@
 use super::backend_registry::{self, Context};
 use super::builtin_registry;
-use crate::ast::{ASTNode, BackendExpr, Program};
+use super::dep_cache;
+use crate::ast::{ASTNode, BackendExpr, CallExpr, Program, StrandAccessExpr};
 use crate::utils::Result;
 use crate::Env;
+use crate::utils::error::ResolveError;
 use crate::WeftError;
-use petgraph::algo::toposort;
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::{DfsPostOrder, EdgeRef, Reversed};
 use petgraph::Direction;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EdgeType {
+    /// A literal 1:1 edge from the original source dependency graph.
     Normal,
     Reference,
+    /// A same-context edge reached through the context-duplication
+    /// machinery in `phase3_build_typed_edges`, rather than a direct edge
+    /// between the original (pre-duplication) nodes.
+    Indirect,
+    /// Never inserted into the graph itself -- a missing dependency has
+    /// no node for the edge to point at. Tags a `DanglingReference`'s
+    /// classification instead.
+    Missing,
+    /// A read through a one-frame-delay marker (`StrandAccessExpr::delayed`),
+    /// i.e. a declared feedback loop rather than a same-frame dependency.
+    /// Excluded from every `Subgraph`'s execution graph before `toposort`
+    /// runs, so a delay-broken cycle schedules cleanly.
+    Feedback,
+}
+
+/// Visitation state for `RenderGraph::dfs_detect_cycle`'s DFS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DfsVisit {
+    InProgress,
+    Done,
+}
+
+/// A dependency name used in some instance's output expression that
+/// doesn't resolve to any instance binding in the program -- typically a
+/// typo in a strand reference. Recorded during `build_initial_edges`
+/// instead of silently dropped, so `build` can fail with a message naming
+/// both the undefined instance and whoever referenced it.
+#[derive(Debug, Clone)]
+pub struct DanglingReference {
+    pub kind: EdgeType,
+    pub referencing_node: String,
+    pub referencing_output: Option<String>,
+    pub missing_name: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,17 +68,73 @@ pub struct GraphNode {
     pub context: Option<Context>,
     pub outputs: HashMap<String, ASTNode>,
     pub deps: HashSet<String>,
+    /// Subset of `deps` read through a one-frame-delay marker -- a
+    /// declared feedback read rather than an ordinary same-frame
+    /// dependency, per `StrandAccessExpr::delayed`.
+    pub delayed_deps: HashSet<String>,
     pub output_deps: HashMap<String, Vec<(String, String)>>,
     pub required_outputs: HashSet<String>,
     pub is_duplicate: bool,
     pub typed_by_child: Option<String>,
+    /// This node's position among `Program::statements`, recorded by
+    /// `collect_instances`. Used to break ties between independent nodes
+    /// deterministically in `extract_subgraphs`'s Kahn's-algorithm schedule,
+    /// rather than leaving their relative order up to `HashMap` iteration.
+    pub declaration_index: usize,
 }
 
 pub struct RenderGraph {
     graph: DiGraph<GraphNode, EdgeType>,
     node_indices: HashMap<String, NodeIndex>,
     duplicate_into: HashMap<String, HashSet<Context>>,
-    original_edges: Vec<(String, String)>,
+    /// (child/dependency, parent/dependent, is_feedback) triples recorded
+    /// by `build_initial_edges`, replayed by `phase3_build_typed_edges` to
+    /// rebuild typed edges once every node's context is known.
+    original_edges: Vec<(String, String, bool)>,
+    /// Content fingerprint of each node's `outputs` as of the last
+    /// successful `build`/`rebuild`, keyed by instance name. Used by
+    /// `rebuild` to tell which nodes' source expressions actually changed.
+    node_fingerprints: HashMap<String, u64>,
+    /// Each node's recorded read-set (its `deps`) as of the last build,
+    /// i.e. the demanded-computation-graph edges a future `rebuild` walks
+    /// to propagate dirtiness to dependents.
+    node_reads: HashMap<String, HashSet<String>>,
+    /// Undefined instance references found by the most recent
+    /// `build_initial_edges` call.
+    dangling_refs: Vec<DanglingReference>,
+    /// Prior-build contexts loaded from a [`dep_cache::DiskCache`] by
+    /// `build_with_cache`, consumed by the very next `rebuild` call in
+    /// place of the live graph's contexts (which don't exist yet, since
+    /// nothing has been built in this process). Empty outside that path.
+    seeded_contexts: HashMap<String, Context>,
+    /// Per-context set of instance names read directly by a `Backend`
+    /// statement's positional args, rebuilt from scratch by every
+    /// `phase0_initial_typing` call. `extract_subgraphs` treats these as
+    /// the roots of a reverse reachability search, so an instance binding
+    /// nothing ever actually consumes doesn't get scheduled.
+    backend_sinks: HashMap<Context, HashSet<String>>,
+}
+
+/// Reports what a `rebuild` actually had to redo: nodes whose fingerprint
+/// or read-set changed (and are therefore retyped from scratch), plus
+/// nodes that were added or removed outright. Downstream codegen can use
+/// this to limit recompilation to the affected subgraphs instead of
+/// treating every `rebuild` like a fresh `build`.
+#[derive(Debug, Clone, Default)]
+pub struct RebuildDiff {
+    pub dirty: HashSet<String>,
+    pub added: HashSet<String>,
+    pub removed: HashSet<String>,
+}
+
+/// Reports which `Context` subgraphs a `build_with_cache` call was able to
+/// restore from the on-disk cache untouched -- every node in that context
+/// was clean, so `phase0`-`phase3` never ran for it. A downstream backend
+/// can treat a reused context as "already compiled" and skip regenerating
+/// code for it.
+#[derive(Debug, Clone, Default)]
+pub struct CacheReport {
+    pub reused_contexts: HashSet<Context>,
 }
 
 #[derive(Debug)]
@@ -50,6 +143,13 @@ pub struct Subgraph {
     pub graph: DiGraph<GraphNode, ()>,
     pub node_names: Vec<String>,
     pub execution_order: Vec<String>,
+    /// `execution_order` grouped into independent layers: `stages[0]` is
+    /// every node with no in-edges, and `stages[n]` is every node whose
+    /// longest dependency chain has length `n`. Nodes sharing a stage
+    /// provably don't depend on each other, so a threaded or GPU-batched
+    /// scheduler can dispatch a whole stage at once instead of walking
+    /// `execution_order` one node at a time.
+    pub stages: Vec<Vec<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +166,19 @@ pub struct MetaGraph {
     pub context_dag: DiGraph<Context, ()>,
     pub execution_order: Vec<Context>,
     pub references: Vec<Reference>,
+    /// Undefined instance references found while building the graph.
+    /// Always empty on a successfully returned `MetaGraph`, since `build`
+    /// fails before reaching this point when any exist -- kept here so a
+    /// `MetaGraph` fully reflects the classification `build_initial_edges`
+    /// records, for tooling that inspects it directly.
+    pub dangling: Vec<DanglingReference>,
+    /// Feedback (one-frame-delay) edges, both within a single context and
+    /// across contexts, reusing `Reference`'s shape since the runtime
+    /// needs the same from/to-context-and-node information either way.
+    /// These never appear in `references` or in any `Subgraph::graph` --
+    /// the runtime instead double-buffers storage for `to_node`'s output
+    /// so `from_node`'s delayed read sees `to_node`'s prior-frame value.
+    pub feedback: Vec<Reference>,
 }
 
 impl RenderGraph {
@@ -75,26 +188,257 @@ impl RenderGraph {
             node_indices: HashMap::new(),
             duplicate_into: HashMap::new(),
             original_edges: Vec::new(),
+            node_fingerprints: HashMap::new(),
+            node_reads: HashMap::new(),
+            dangling_refs: Vec::new(),
+            seeded_contexts: HashMap::new(),
+            backend_sinks: HashMap::new(),
         }
     }
 
     pub fn build(&mut self, ast: &Program, env: &Env) -> Result<MetaGraph> {
         self.collect_instances(ast, env)?;
         self.build_initial_edges();
+        if !self.dangling_refs.is_empty() {
+            return Err(self.dangling_error());
+        }
+        self.phase0_initial_typing(ast, env)?;
+        self.phase1_type_propagation()?;
+        self.phase2_find_and_process_untyped_components()?;
+        self.phase3_build_typed_edges()?;
+        let meta_graph = self.build_meta_graph()?;
+        self.record_snapshot();
+        Ok(meta_graph)
+    }
+
+    /// Like `build`, but reuses typing work from the previous build/rebuild
+    /// for any node whose `outputs` and read-set (`deps`) are unchanged.
+    ///
+    /// Modeled on Adapton's demanded-computation-graph: each node carries a
+    /// fingerprint of its own outputs, and `node_reads` records the edges
+    /// of a side dependency graph (what each node read to get typed). A
+    /// node is "dirty" if its fingerprint changed, it's new, or anything in
+    /// its read-set is dirty -- propagated backward along those edges to a
+    /// fixpoint, exactly like Adapton's dirty/clean traversal. Clean nodes
+    /// have their previously-resolved `context` reinstated before typing
+    /// runs, which lets `phase1`/`phase2` skip over them (both already
+    /// short-circuit on `context.is_some()`), giving an early cutoff for
+    /// everything demand doesn't actually require re-deriving.
+    ///
+    /// Instance collection and edge-building themselves still run in full
+    /// on every call -- only the typing phases benefit from the cache --
+    /// so `rebuild` is cheaper than `build` in proportion to how much of
+    /// the graph is actually unchanged, not free.
+    pub fn rebuild(&mut self, ast: &Program, env: &Env) -> Result<(MetaGraph, RebuildDiff)> {
+        let prev_fingerprints = std::mem::take(&mut self.node_fingerprints);
+        let prev_reads = std::mem::take(&mut self.node_reads);
+        let prev_contexts: HashMap<String, Context> = if self.node_indices.is_empty() {
+            // Nothing has been built in this process yet -- if
+            // `build_with_cache` seeded contexts from a prior run's disk
+            // cache, this is where they get consumed instead of the (empty)
+            // live graph's.
+            std::mem::take(&mut self.seeded_contexts)
+        } else {
+            self.node_indices
+                .iter()
+                .filter_map(|(name, &idx)| self.graph[idx].context.map(|ctx| (name.clone(), ctx)))
+                .collect()
+        };
+        // Normally the same set as `node_indices.keys()`; falls back to the
+        // cached fingerprints' keys so a `build_with_cache` bootstrapping
+        // from a disk cache into a fresh `RenderGraph` (empty `node_indices`)
+        // still diffs against the prior run's node set instead of treating
+        // every node as newly added.
+        let prev_names: HashSet<String> = if self.node_indices.is_empty() {
+            prev_fingerprints.keys().cloned().collect()
+        } else {
+            self.node_indices.keys().cloned().collect()
+        };
+
+        self.graph = DiGraph::new();
+        self.node_indices = HashMap::new();
+        self.duplicate_into = HashMap::new();
+        self.original_edges = Vec::new();
+
+        self.collect_instances(ast, env)?;
+        self.build_initial_edges();
+        if !self.dangling_refs.is_empty() {
+            return Err(self.dangling_error());
+        }
+
+        let new_names: HashSet<String> = self.node_indices.keys().cloned().collect();
+        let added: HashSet<String> = new_names.difference(&prev_names).cloned().collect();
+        let removed: HashSet<String> = prev_names.difference(&new_names).cloned().collect();
+
+        let mut dirty: HashSet<String> = added.clone();
+        for name in &new_names {
+            let &idx = self.node_indices.get(name).unwrap();
+            let fingerprint = Self::fingerprint_node(&self.graph[idx]);
+            if prev_fingerprints.get(name) != Some(&fingerprint)
+                || prev_reads.get(name) != Some(&self.graph[idx].deps)
+            {
+                dirty.insert(name.clone());
+            }
+        }
+
+        loop {
+            let mut grew = false;
+            for name in &new_names {
+                if dirty.contains(name) {
+                    continue;
+                }
+                let &idx = self.node_indices.get(name).unwrap();
+                if self.graph[idx].deps.iter().any(|dep| dirty.contains(dep)) {
+                    dirty.insert(name.clone());
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        for name in &new_names {
+            if dirty.contains(name) {
+                continue;
+            }
+            if let Some(&ctx) = prev_contexts.get(name) {
+                let &idx = self.node_indices.get(name).unwrap();
+                self.graph[idx].context = Some(ctx);
+            }
+        }
+
         self.phase0_initial_typing(ast, env)?;
         self.phase1_type_propagation()?;
         self.phase2_find_and_process_untyped_components()?;
         self.phase3_build_typed_edges()?;
-        self.build_meta_graph()
+        let meta_graph = self.build_meta_graph()?;
+        self.record_snapshot();
+
+        Ok((
+            meta_graph,
+            RebuildDiff {
+                dirty,
+                added,
+                removed,
+            },
+        ))
+    }
+
+    /// Like `rebuild`, but the "previous build" it diffs against is loaded
+    /// from `cache_path` rather than this `RenderGraph`'s own in-memory
+    /// state -- the on-disk equivalent of rustc's persisted dep-graph,
+    /// letting an early-cutoff rebuild happen on the very first `build` of
+    /// a fresh process, not just the second one within it.
+    ///
+    /// A cache written by a different version of `backend_registry` or
+    /// `builtin_registry` (tracked via `dep_cache::registry_fingerprint`)
+    /// is ignored wholesale, since both feed `phase0_initial_typing` and a
+    /// stale classification there would silently wrong-type a node.
+    pub fn build_with_cache(
+        &mut self,
+        ast: &Program,
+        env: &Env,
+        cache_path: &std::path::Path,
+    ) -> Result<(MetaGraph, CacheReport)> {
+        let cache = dep_cache::DiskCache::load(cache_path);
+        if cache.registry_fingerprint == dep_cache::registry_fingerprint() {
+            self.node_fingerprints = cache
+                .nodes
+                .iter()
+                .map(|(name, cached)| (name.clone(), cached.fingerprint))
+                .collect();
+            self.node_reads = cache
+                .nodes
+                .iter()
+                .map(|(name, cached)| (name.clone(), cached.reads.clone()))
+                .collect();
+            self.seeded_contexts = cache
+                .nodes
+                .iter()
+                .map(|(name, cached)| (name.clone(), cached.context))
+                .collect();
+        }
+
+        let (meta_graph, diff) = self.rebuild(ast, env)?;
+
+        let reused_contexts = meta_graph
+            .subgraphs
+            .values()
+            .filter(|subgraph| {
+                subgraph
+                    .node_names
+                    .iter()
+                    .all(|name| !diff.dirty.contains(name) && !diff.added.contains(name))
+            })
+            .map(|subgraph| subgraph.context)
+            .collect();
+
+        let nodes = self
+            .node_indices
+            .iter()
+            .filter_map(|(name, &idx)| {
+                let context = self.graph[idx].context?;
+                Some((
+                    name.clone(),
+                    dep_cache::CachedNode {
+                        fingerprint: *self.node_fingerprints.get(name)?,
+                        reads: self.node_reads.get(name).cloned().unwrap_or_default(),
+                        context,
+                    },
+                ))
+            })
+            .collect();
+        dep_cache::DiskCache {
+            registry_fingerprint: dep_cache::registry_fingerprint(),
+            nodes,
+        }
+        .save(cache_path)?;
+
+        Ok((meta_graph, CacheReport { reused_contexts }))
+    }
+
+    /// Hashes a node's `outputs` (its own source expressions, ignoring
+    /// dependents) so `rebuild` can detect when a node's definition itself
+    /// changed. `ASTNode` has no `Hash` impl, so this hashes the `Debug`
+    /// rendering as a cheap stand-in -- fine here since the fingerprint is
+    /// only ever compared to another fingerprint computed the same way,
+    /// never decoded.
+    fn fingerprint_node(node: &GraphNode) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = DefaultHasher::new();
+        let mut names: Vec<&String> = node.outputs.keys().collect();
+        names.sort();
+        for name in names {
+            name.hash(&mut hasher);
+            format!("{:?}", node.outputs[name]).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Snapshots fingerprints and read-sets after a successful build, so
+    /// the next `rebuild` has something to diff against.
+    fn record_snapshot(&mut self) {
+        let mut fingerprints = HashMap::new();
+        let mut reads = HashMap::new();
+        for (name, &idx) in &self.node_indices {
+            fingerprints.insert(name.clone(), Self::fingerprint_node(&self.graph[idx]));
+            reads.insert(name.clone(), self.graph[idx].deps.clone());
+        }
+        self.node_fingerprints = fingerprints;
+        self.node_reads = reads;
     }
 
     fn collect_instances(&mut self, ast: &Program, env: &Env) -> Result<()> {
-        for stmt in &ast.statements {
+        for (declaration_index, stmt) in ast.statements.iter().enumerate() {
             if let ASTNode::InstanceBinding(bind) = stmt {
                 let node_type = check_node_type(&bind.expr, env);
                 let mut outputs = HashMap::new();
                 let mut output_deps = HashMap::new();
                 let mut all_deps = HashSet::new();
+                let mut all_delayed_deps = HashSet::new();
 
                 if let ASTNode::Tuple(tuple_expr) = &*bind.expr {
                     for (i, output_name) in bind.outputs.iter().enumerate() {
@@ -104,12 +448,14 @@ impl RenderGraph {
                             outputs.insert(output_name.clone(), expr.clone());
 
                             let mut instance_deps = HashSet::new();
+                            let mut delayed_deps = HashSet::new();
                             let mut output_level_deps = Vec::new();
-                            find_deps_in_expr(expr, &mut instance_deps);
+                            find_deps_in_expr(expr, &mut instance_deps, &mut delayed_deps);
 
                             find_output_deps_in_expr(expr, &mut output_level_deps);
 
                             all_deps.extend(instance_deps);
+                            all_delayed_deps.extend(delayed_deps);
 
                             output_deps.insert(output_name.clone(), output_level_deps);
                         }
@@ -119,12 +465,14 @@ impl RenderGraph {
                         outputs.insert(output_name.clone(), (*bind.expr).clone());
 
                         let mut instance_deps = HashSet::new();
+                        let mut delayed_deps = HashSet::new();
                         let mut output_level_deps = Vec::new();
-                        find_deps_in_expr(&bind.expr, &mut instance_deps);
+                        find_deps_in_expr(&bind.expr, &mut instance_deps, &mut delayed_deps);
 
                         find_output_deps_in_expr(&bind.expr, &mut output_level_deps);
 
                         all_deps.extend(instance_deps);
+                        all_delayed_deps.extend(delayed_deps);
 
                         output_deps.insert(output_name.clone(), output_level_deps);
                     }
@@ -137,10 +485,12 @@ impl RenderGraph {
                     context: None,
                     outputs,
                     deps: all_deps,
+                    delayed_deps: all_delayed_deps,
                     output_deps,
                     required_outputs: HashSet::new(),
                     is_duplicate: false,
                     typed_by_child: None,
+                    declaration_index,
                 };
 
                 let idx = self.graph.add_node(graph_node);
@@ -153,27 +503,70 @@ impl RenderGraph {
 
     fn build_initial_edges(&mut self) {
         let mut edges_to_add = Vec::new();
+        self.dangling_refs.clear();
 
         for (name, &node_idx) in &self.node_indices {
             let deps = self.graph[node_idx].deps.clone();
             for dep_name in deps {
                 if self.node_indices.contains_key(&dep_name) {
-                    self.original_edges.push((dep_name.clone(), name.clone()));
-                    edges_to_add.push((dep_name, name.clone()));
+                    let is_feedback = self.graph[node_idx].delayed_deps.contains(&dep_name);
+                    self.original_edges
+                        .push((dep_name.clone(), name.clone(), is_feedback));
+                    edges_to_add.push((dep_name, name.clone(), is_feedback));
+                } else {
+                    let referencing_output = self.graph[node_idx]
+                        .output_deps
+                        .iter()
+                        .find(|(_, refs)| refs.iter().any(|(inst, _)| inst == &dep_name))
+                        .map(|(output, _)| output.clone());
+
+                    self.dangling_refs.push(DanglingReference {
+                        kind: EdgeType::Missing,
+                        referencing_node: name.clone(),
+                        referencing_output,
+                        missing_name: dep_name,
+                    });
                 }
             }
         }
 
-        for (child, parent) in edges_to_add {
+        for (child, parent, is_feedback) in edges_to_add {
             if let (Some(&child_idx), Some(&parent_idx)) = (
                 self.node_indices.get(&child),
                 self.node_indices.get(&parent),
             ) {
-                self.graph.add_edge(child_idx, parent_idx, EdgeType::Normal);
+                let edge_type = if is_feedback {
+                    EdgeType::Feedback
+                } else {
+                    EdgeType::Normal
+                };
+                self.graph.add_edge(child_idx, parent_idx, edge_type);
             }
         }
     }
 
+    /// Formats every recorded dangling reference into a single actionable
+    /// diagnostic naming the undefined instance and whoever referenced it.
+    fn dangling_error(&self) -> WeftError {
+        let details = self
+            .dangling_refs
+            .iter()
+            .map(|d| match &d.referencing_output {
+                Some(output) => format!(
+                    "  {}@{} references undefined instance `{}`",
+                    d.referencing_node, output, d.missing_name
+                ),
+                None => format!(
+                    "  {} references undefined instance `{}`",
+                    d.referencing_node, d.missing_name
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        WeftError::Resolve(ResolveError::UndefinedInstances(details))
+    }
+
     fn phase0_initial_typing(&mut self, ast: &Program, env: &Env) -> Result<()> {
         // Phase 0a: Type inherent builtins from builtin_registry
         let all_nodes: Vec<NodeIndex> = self.graph.node_indices().collect();
@@ -190,10 +583,11 @@ impl RenderGraph {
         }
 
         // Phase 0b: Type from backend statements
+        self.backend_sinks = HashMap::new();
         for stmt in &ast.statements {
             if let ASTNode::Backend(backend) = stmt {
                 let context = backend_registry::get_context(&backend.context).ok_or_else(|| {
-                    WeftError::Runtime(format!("Unknown backend: {}", backend.context))
+                    WeftError::Resolve(ResolveError::UnknownBackend(backend.context.clone()))
                 })?;
 
                 for arg in &backend.positional_args {
@@ -208,12 +602,12 @@ impl RenderGraph {
         match expr {
             ASTNode::StrandAccess(access) => {
                 if let ASTNode::Var(var) = &*access.base {
-                    self.type_node(&var.name, context);
+                    self.type_node(var.name.resolve(), context);
                 }
             }
             ASTNode::StrandRemap(remap) => {
                 if let ASTNode::Var(var) = &*remap.base {
-                    self.type_node(&var.name, context);
+                    self.type_node(var.name.resolve(), context);
                 }
                 for mapping in &remap.mappings {
                     self.type_expr_as(&mapping.expr, context);
@@ -236,6 +630,12 @@ impl RenderGraph {
                 self.type_expr_as(&if_expr.then_expr, context);
                 self.type_expr_as(&if_expr.else_expr, context);
             }
+            ASTNode::Match(match_expr) => {
+                self.type_expr_as(&match_expr.scrutinee, context);
+                for arm in &match_expr.arms {
+                    self.type_expr_as(&arm.body, context);
+                }
+            }
             ASTNode::Tuple(tuple) => {
                 for item in &tuple.items {
                     self.type_expr_as(item, context);
@@ -253,6 +653,10 @@ impl RenderGraph {
         if let Some(&idx) = self.node_indices.get(name) {
             self.graph[idx].context = Some(context);
         }
+        self.backend_sinks
+            .entry(context)
+            .or_default()
+            .insert(name.to_string());
     }
 
     pub fn get_node(&self, name: &str) -> Option<&GraphNode> {
@@ -264,7 +668,7 @@ impl RenderGraph {
         for expr in node.outputs.values() {
             if let ASTNode::Call(call) = expr {
                 if let ASTNode::Var(var) = &*call.name {
-                    return Some(var.name.clone());
+                    return Some(var.name.resolve().to_string());
                 }
             }
         }
@@ -272,74 +676,86 @@ impl RenderGraph {
     }
 
     fn phase1_type_propagation(&mut self) -> Result<()> {
-        // Phase 1a: Bottom-up propagation (from dependents to dependencies)
-        let mut changed = true;
-        while changed {
-            changed = false;
-            let all_nodes: Vec<NodeIndex> = self.graph.node_indices().collect();
-
-            for node_idx in all_nodes {
-                if self.graph[node_idx].context.is_some() {
-                    continue;
-                }
+        // Phase 1a: Bottom-up -- an untyped node adopts the context of its
+        // dependents (nodes that reference it via an Outgoing edge), once
+        // exactly one such context has reached it.
+        self.propagate_contexts(Direction::Incoming);
 
-                let dependents: Vec<NodeIndex> = self
-                    .graph
-                    .neighbors_directed(node_idx, Direction::Outgoing)
-                    .collect();
+        // Phase 1b: Top-down -- an untyped node adopts the context of its
+        // dependencies (Incoming edges), catching nodes unreachable from
+        // phase 1a.
+        self.propagate_contexts(Direction::Outgoing);
 
-                if dependents.is_empty() {
-                    continue;
-                }
+        Ok(())
+    }
 
-                let dependent_contexts: HashSet<Context> = dependents
-                    .iter()
-                    .filter_map(|&idx| self.graph[idx].context)
-                    .collect();
+    /// Level-synchronous context propagation, replacing the previous
+    /// O(iterations x nodes) "rescan everything until nothing changes"
+    /// fixpoint. `Context` is a small closed enum, so each node's
+    /// candidate-context set is tracked as a bitmask (one bit per
+    /// context). Propagation runs in waves: every node in the current
+    /// `frontier` OR's its bit into each untyped neighbor reached by
+    /// `push_direction`, *all in the same wave before any of those
+    /// neighbors are resolved* -- so two frontier nodes that reach the
+    /// same neighbor together are seen together, the same way the old
+    /// rescan read every already-typed dependent's `context` into one
+    /// `HashSet` per node per sweep rather than committing to whichever
+    /// arrived first. Only once a wave finishes does a neighbor whose
+    /// mask now has exactly one bit set become concretely typed (the
+    /// same rule as the old `dependent_contexts.len() == 1` check) and
+    /// join the next wave's frontier; a neighbor left with more than one
+    /// bit is a multi-context candidate for phase 2's duplication logic,
+    /// and -- matching the old rescan, which only ever read already-
+    /// *typed* dependents' contexts, so an ambiguous node never
+    /// contributed one downstream -- it never forwards its mask onward.
+    ///
+    /// `push_direction` names the edge direction a typed node pushes its
+    /// bit *along*: `Incoming` drives phase 1a (typed node -> the nodes it
+    /// depends on), `Outgoing` drives phase 1b (typed node -> the nodes
+    /// that depend on it).
+    fn propagate_contexts(&mut self, push_direction: Direction) {
+        let mut masks: HashMap<NodeIndex, u64> = HashMap::new();
+        let mut frontier: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| self.graph[idx].context.is_some())
+            .collect();
 
-                if dependent_contexts.len() == 1 {
-                    let context = *dependent_contexts.iter().next().unwrap();
-                    self.graph[node_idx].context = Some(context);
-                    changed = true;
-                }
-            }
+        for &idx in &frontier {
+            masks.insert(idx, context_bit(self.graph[idx].context.unwrap()));
         }
 
-        // Phase 1b: Top-down propagation (from dependencies to dependents)
-        // This handles unreachable nodes that depend on typed nodes
-        changed = true;
-        while changed {
-            changed = false;
-            let all_nodes: Vec<NodeIndex> = self.graph.node_indices().collect();
-
-            for node_idx in all_nodes {
-                if self.graph[node_idx].context.is_some() {
-                    continue;
-                }
-
-                let dependencies: Vec<NodeIndex> = self
-                    .graph
-                    .neighbors_directed(node_idx, Direction::Incoming)
-                    .collect();
-
-                if dependencies.is_empty() {
-                    continue;
-                }
+        while !frontier.is_empty() {
+            let mut grown: HashSet<NodeIndex> = HashSet::new();
 
-                let dependency_contexts: HashSet<Context> = dependencies
-                    .iter()
-                    .filter_map(|&idx| self.graph[idx].context)
-                    .collect();
+            for &idx in &frontier {
+                let mask = masks[&idx];
+                for neighbor in self.graph.neighbors_directed(idx, push_direction) {
+                    if self.graph[neighbor].context.is_some() {
+                        continue;
+                    }
 
-                if dependency_contexts.len() == 1 {
-                    let context = *dependency_contexts.iter().next().unwrap();
-                    self.graph[node_idx].context = Some(context);
-                    changed = true;
+                    let entry = masks.entry(neighbor).or_insert(0);
+                    if *entry & mask != mask {
+                        *entry |= mask;
+                        grown.insert(neighbor);
+                    }
                 }
             }
-        }
 
-        Ok(())
+            frontier = grown
+                .into_iter()
+                .filter(|&idx| {
+                    let mask = masks[&idx];
+                    if mask.count_ones() == 1 {
+                        self.graph[idx].context = context_from_bit(mask);
+                        true
+                    } else {
+                        false
+                    }
+                })
+                .collect();
+        }
     }
 
     fn phase2_find_and_process_untyped_components(&mut self) -> Result<()> {
@@ -480,10 +896,12 @@ impl RenderGraph {
                         context: Some(*context),
                         outputs: old_node.outputs.clone(),
                         deps: old_node.deps.clone(),
+                        delayed_deps: old_node.delayed_deps.clone(),
                         output_deps: old_node.output_deps.clone(),
                         required_outputs: old_node.required_outputs.clone(),
                         is_duplicate: true,
                         typed_by_child: old_node.typed_by_child.clone(),
+                        declaration_index: old_node.declaration_index,
                     };
                     let new_idx = new_graph.add_node(new_node);
                     new_node_indices.insert(new_name, new_idx);
@@ -506,7 +924,7 @@ impl RenderGraph {
 
         let original_edges = self.original_edges.clone();
 
-        for (child_name, parent_name) in original_edges {
+        for (child_name, parent_name, is_feedback) in original_edges {
             let child_was_duplicated = self.duplicate_into.contains_key(&child_name);
             let parent_was_duplicated = self.duplicate_into.contains_key(&parent_name);
 
@@ -523,7 +941,12 @@ impl RenderGraph {
                         self.node_indices.get(&child_concrete),
                         self.node_indices.get(&parent_concrete),
                     ) {
-                        self.graph.add_edge(child_idx, parent_idx, EdgeType::Normal);
+                        let edge_type = if is_feedback {
+                            EdgeType::Feedback
+                        } else {
+                            EdgeType::Indirect
+                        };
+                        self.graph.add_edge(child_idx, parent_idx, edge_type);
                     }
                 }
             } else {
@@ -544,7 +967,13 @@ impl RenderGraph {
                             continue;
                         }
 
-                        self.add_edge(child_idx, parent_idx, &child_name, &parent_name)?;
+                        self.add_edge(
+                            child_idx,
+                            parent_idx,
+                            &child_name,
+                            &parent_name,
+                            is_feedback,
+                        )?;
                     }
                 }
             }
@@ -577,11 +1006,14 @@ impl RenderGraph {
         parent_idx: NodeIndex,
         _child_original: &str,
         _parent_original: &str,
+        is_feedback: bool,
     ) -> Result<()> {
         let child_context = self.graph[child_idx].context;
         let parent_context = self.graph[parent_idx].context;
 
-        let edge_type = if child_context == parent_context {
+        let edge_type = if is_feedback {
+            EdgeType::Feedback
+        } else if child_context == parent_context {
             EdgeType::Normal
         } else {
             EdgeType::Reference
@@ -591,7 +1023,9 @@ impl RenderGraph {
         Ok(())
     }
     fn build_meta_graph(&self) -> Result<MetaGraph> {
-        let (subgraphs, references) = self.extract_subgraphs()?;
+        self.detect_unmarked_cycles()?;
+
+        let (subgraphs, references, feedback) = self.extract_subgraphs()?;
         let (context_dag, execution_order) = self.build_context_dag(&subgraphs, &references)?;
 
         Ok(MetaGraph {
@@ -599,10 +1033,72 @@ impl RenderGraph {
             context_dag,
             execution_order,
             references,
+            dangling: self.dangling_refs.clone(),
+            feedback,
         })
     }
 
-    fn extract_subgraphs(&self) -> Result<(HashMap<Context, Subgraph>, Vec<Reference>)> {
+    /// Walks the full typed graph with an explicit DFS, looking for a
+    /// back-edge (an edge to a node still on the current DFS stack) that
+    /// isn't `EdgeType::Feedback`. A declared feedback edge is allowed to
+    /// close a loop; anything else closing a loop is a genuine cycle, and
+    /// gets reported with its full path rather than `toposort`'s generic
+    /// "cycle in subgraph" error once the graph is later split up.
+    fn detect_unmarked_cycles(&self) -> Result<()> {
+        let mut visited: HashMap<NodeIndex, DfsVisit> = HashMap::new();
+        let mut stack: Vec<NodeIndex> = Vec::new();
+
+        for start in self.graph.node_indices() {
+            if !visited.contains_key(&start) {
+                self.dfs_detect_cycle(start, &mut visited, &mut stack)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn dfs_detect_cycle(
+        &self,
+        node: NodeIndex,
+        visited: &mut HashMap<NodeIndex, DfsVisit>,
+        stack: &mut Vec<NodeIndex>,
+    ) -> Result<()> {
+        visited.insert(node, DfsVisit::InProgress);
+        stack.push(node);
+
+        for edge in self.graph.edges(node) {
+            let target = edge.target();
+            if *edge.weight() == EdgeType::Feedback {
+                continue;
+            }
+
+            match visited.get(&target) {
+                Some(DfsVisit::InProgress) => {
+                    let start = stack.iter().position(|&idx| idx == target).unwrap();
+                    let mut path: Vec<String> = stack[start..]
+                        .iter()
+                        .map(|&idx| self.graph[idx].instance_name.clone())
+                        .collect();
+                    path.push(self.graph[target].instance_name.clone());
+
+                    return Err(WeftError::Runtime(format!(
+                        "cycle without a feedback/delay marker: {}",
+                        path.join(" -> ")
+                    )));
+                }
+                Some(DfsVisit::Done) => {}
+                None => self.dfs_detect_cycle(target, visited, stack)?,
+            }
+        }
+
+        stack.pop();
+        visited.insert(node, DfsVisit::Done);
+        Ok(())
+    }
+
+    fn extract_subgraphs(
+        &self,
+    ) -> Result<(HashMap<Context, Subgraph>, Vec<Reference>, Vec<Reference>)> {
         let mut nodes_by_context: HashMap<Context, Vec<NodeIndex>> = HashMap::new();
 
         for idx in self.graph.node_indices() {
@@ -613,6 +1109,7 @@ impl RenderGraph {
 
         let mut subgraphs = HashMap::new();
         let mut references = Vec::new();
+        let mut feedback = Vec::new();
 
         for (context, node_indices) in nodes_by_context {
             let mut subgraph = DiGraph::new();
@@ -629,13 +1126,14 @@ impl RenderGraph {
             for &old_idx in &node_indices {
                 for edge in self.graph.edges(old_idx) {
                     match edge.weight() {
-                        EdgeType::Normal => {
+                        EdgeType::Normal | EdgeType::Indirect => {
                             if let (Some(&src), Some(&tgt)) =
                                 (old_to_new.get(&old_idx), old_to_new.get(&edge.target()))
                             {
                                 subgraph.add_edge(src, tgt, ());
                             }
                         }
+                        EdgeType::Missing => {}
                         EdgeType::Reference => {
                             let from_node = &self.graph[old_idx].instance_name;
                             let to_node = &self.graph[edge.target()].instance_name;
@@ -653,12 +1151,51 @@ impl RenderGraph {
                                 });
                             }
                         }
+                        EdgeType::Feedback => {
+                            // A declared one-frame-delay read: excluded from
+                            // this context's execution graph (so toposort
+                            // never sees the cycle it closes) and recorded as
+                            // a Reference instead, same as a cross-context
+                            // dependency, so the runtime can double-buffer
+                            // `to_node`'s output for the delayed read.
+                            let from_node = &self.graph[old_idx].instance_name;
+                            let to_node = &self.graph[edge.target()].instance_name;
+
+                            if let Some(to_context) = self.graph[edge.target()].context {
+                                feedback.push(Reference {
+                                    from_context: to_context,
+                                    from_node: to_node.clone(),
+                                    to_context: context,
+                                    to_node: from_node.clone(),
+                                });
+                            }
+                        }
                     }
                 }
             }
 
-            let execution_order = toposort(&subgraph, None)
-                .map_err(|_| WeftError::Runtime(format!("Cycle in {} subgraph", context.name())))?
+            let (subgraph, node_names) = self.prune_dead_strands(subgraph, node_names, context);
+
+            let order_indices =
+                deterministic_toposort(&subgraph, |idx| subgraph[idx].declaration_index)
+                    .ok_or_else(|| describe_subgraph_cycle(&subgraph, context))?;
+
+            let mut levels: HashMap<NodeIndex, usize> = HashMap::new();
+            for &idx in &order_indices {
+                let level = subgraph
+                    .neighbors_directed(idx, Direction::Incoming)
+                    .map(|pred| levels[&pred] + 1)
+                    .max()
+                    .unwrap_or(0);
+                levels.insert(idx, level);
+            }
+            let stage_count = levels.values().copied().max().map_or(0, |m| m + 1);
+            let mut stages: Vec<Vec<String>> = vec![Vec::new(); stage_count];
+            for &idx in &order_indices {
+                stages[levels[&idx]].push(subgraph[idx].instance_name.clone());
+            }
+
+            let execution_order = order_indices
                 .into_iter()
                 .map(|idx| subgraph[idx].instance_name.clone())
                 .collect();
@@ -670,11 +1207,87 @@ impl RenderGraph {
                     graph: subgraph,
                     node_names,
                     execution_order,
+                    stages,
                 },
             );
         }
 
-        Ok((subgraphs, references))
+        Ok((subgraphs, references, feedback))
+    }
+
+    /// Drops every node in `subgraph` that no `Backend` statement in this
+    /// context ever demands, directly or transitively: an instance binding
+    /// nobody reads is typed and duplicated like any other node, but has no
+    /// business being scheduled.
+    ///
+    /// Reachability is a reverse BFS from `self.backend_sinks[context]`
+    /// (the strand-accesses that actually appear in this context's backend
+    /// args) walking `Direction::Incoming`, i.e. from a consumer to what it
+    /// depends on. `subgraph`'s edges are already context-local (built from
+    /// `Normal`/`Indirect` only -- see the match above), so this can never
+    /// cross into another context's nodes; a node kept alive only by a
+    /// cross-context `Reference` is always independently typed as a direct
+    /// sink of its own context too (see `add_edge`: a `Reference` edge only
+    /// ever connects two nodes that were each typed from their own
+    /// backend), so pruning here can't strand one.
+    fn prune_dead_strands(
+        &self,
+        subgraph: DiGraph<GraphNode, ()>,
+        node_names: Vec<String>,
+        context: Context,
+    ) -> (DiGraph<GraphNode, ()>, Vec<String>) {
+        let sink_names = self.backend_sinks.get(&context);
+        let sinks = subgraph.node_indices().filter(|&idx| {
+            let node = &subgraph[idx];
+            sink_names.is_some_and(|names| {
+                names.contains(&node.instance_name)
+                    || node
+                        .original_name
+                        .as_deref()
+                        .is_some_and(|orig| names.contains(orig))
+            })
+        });
+
+        let mut reachable: HashSet<NodeIndex> = HashSet::new();
+        let mut queue: VecDeque<NodeIndex> = VecDeque::new();
+        for idx in sinks {
+            if reachable.insert(idx) {
+                queue.push_back(idx);
+            }
+        }
+        while let Some(idx) = queue.pop_front() {
+            for pred in subgraph.neighbors_directed(idx, Direction::Incoming) {
+                if reachable.insert(pred) {
+                    queue.push_back(pred);
+                }
+            }
+        }
+
+        if reachable.len() == subgraph.node_count() {
+            return (subgraph, node_names);
+        }
+
+        let mut pruned = DiGraph::new();
+        let mut old_to_new = HashMap::new();
+        let mut kept_names = Vec::new();
+        for idx in subgraph.node_indices() {
+            if reachable.contains(&idx) {
+                let node = subgraph[idx].clone();
+                kept_names.push(node.instance_name.clone());
+                old_to_new.insert(idx, pruned.add_node(node));
+            }
+        }
+        for idx in subgraph.node_indices() {
+            if reachable.contains(&idx) {
+                for edge in subgraph.edges(idx) {
+                    if reachable.contains(&edge.target()) {
+                        pruned.add_edge(old_to_new[&idx], old_to_new[&edge.target()], ());
+                    }
+                }
+            }
+        }
+
+        (pruned, kept_names)
     }
 
     fn build_context_dag(
@@ -728,74 +1341,251 @@ impl RenderGraph {
             added_edges.insert(edge);
         }
 
-        let execution_order = toposort(&context_dag, None)
-            .map_err(|_| WeftError::Runtime("Circular dependency between contexts".to_string()))?
-            .into_iter()
-            .map(|idx| context_dag[idx])
-            .collect();
+        let execution_order =
+            deterministic_toposort(&context_dag, |idx| context_dag[idx].priority())
+                .ok_or_else(|| describe_context_cycle(&context_dag, references))?
+                .into_iter()
+                .map(|idx| context_dag[idx])
+                .collect();
 
         Ok((context_dag, execution_order))
     }
 }
 
-fn find_deps_in_expr(expr: &ASTNode, deps: &mut HashSet<String>) {
+/// A Kahn's-algorithm topological sort that breaks ties between
+/// simultaneously-ready nodes (in-degree 0) deterministically, by `key`
+/// rather than whatever order `petgraph::algo::toposort`'s DFS happens to
+/// visit them in. Used for both `Subgraph::execution_order` (keyed on
+/// `GraphNode::declaration_index`) and the context DAG's order (keyed on
+/// `Context::priority`), so independent-node ordering is reproducible
+/// across runs instead of depending on `HashMap`/DFS iteration order.
+/// Returns `None` if fewer than all nodes could be scheduled, i.e. a cycle.
+fn deterministic_toposort<N, E, K: Ord>(
+    graph: &DiGraph<N, E>,
+    key: impl Fn(NodeIndex) -> K,
+) -> Option<Vec<NodeIndex>> {
+    let mut in_degree: HashMap<NodeIndex, usize> = graph
+        .node_indices()
+        .map(|idx| {
+            (
+                idx,
+                graph.neighbors_directed(idx, Direction::Incoming).count(),
+            )
+        })
+        .collect();
+
+    let mut ready: BinaryHeap<Reverse<(K, NodeIndex)>> = in_degree
+        .iter()
+        .filter(|&(_, &degree)| degree == 0)
+        .map(|(&idx, _)| Reverse((key(idx), idx)))
+        .collect();
+
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(Reverse((_, idx))) = ready.pop() {
+        order.push(idx);
+        for successor in graph.neighbors_directed(idx, Direction::Outgoing) {
+            let degree = in_degree.get_mut(&successor).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push(Reverse((key(successor), successor)));
+            }
+        }
+    }
+
+    (order.len() == graph.node_count()).then_some(order)
+}
+
+/// Builds a precise error for a `toposort` failure within one context's
+/// subgraph: runs `tarjan_scc` to find the strongly-connected component(s)
+/// that close the loop (size > 1, or a single node with a self-loop) and
+/// names their member instances, rather than just naming the context.
+///
+/// In practice `RenderGraph::detect_unmarked_cycles` already rejects any
+/// cycle lacking a `Feedback` edge before `extract_subgraphs` runs, so this
+/// is a defensive fallback for a cycle this function's own edge filtering
+/// somehow let through rather than a path expected to be hit.
+fn describe_subgraph_cycle(subgraph: &DiGraph<GraphNode, ()>, context: Context) -> WeftError {
+    let cycles: Vec<String> = tarjan_scc(subgraph)
+        .iter()
+        .filter(|component| {
+            component.len() > 1 || subgraph.find_edge(component[0], component[0]).is_some()
+        })
+        .map(|component| {
+            let mut names: Vec<String> = component
+                .iter()
+                .map(|&idx| subgraph[idx].instance_name.clone())
+                .collect();
+            names.sort();
+            names.join(", ")
+        })
+        .collect();
+
+    WeftError::Runtime(format!(
+        "cycle in {} subgraph, involving: {}",
+        context.name(),
+        cycles.join(" | ")
+    ))
+}
+
+/// Like `describe_subgraph_cycle`, but for a `toposort` failure over the
+/// context DAG: names the cyclic `Context`s and the `Reference`s between
+/// them that closed the loop, instead of a single opaque line. `Reference`
+/// edges are real edges in the typed graph too, so `detect_unmarked_cycles`
+/// already rejects any cross-context cycle before this point -- this is a
+/// defensive fallback for the same reason `describe_subgraph_cycle` is.
+fn describe_context_cycle(
+    context_dag: &DiGraph<Context, ()>,
+    references: &[Reference],
+) -> WeftError {
+    let cycles: Vec<String> = tarjan_scc(context_dag)
+        .iter()
+        .filter(|component| component.len() > 1)
+        .map(|component| {
+            let contexts: HashSet<Context> =
+                component.iter().map(|&idx| context_dag[idx]).collect();
+            let mut names: Vec<&str> = contexts.iter().map(|c| c.name()).collect();
+            names.sort();
+
+            let mut edges: Vec<String> = references
+                .iter()
+                .filter(|r| contexts.contains(&r.from_context) && contexts.contains(&r.to_context))
+                .map(|r| {
+                    format!(
+                        "{}({}) depends on {}({})",
+                        r.from_node,
+                        r.from_context.name(),
+                        r.to_node,
+                        r.to_context.name()
+                    )
+                })
+                .collect();
+            edges.sort();
+            edges.dedup();
+
+            format!("{} [{}]", names.join(", "), edges.join("; "))
+        })
+        .collect();
+
+    WeftError::Runtime(format!(
+        "circular dependency between contexts: {}",
+        cycles.join(" | ")
+    ))
+}
+
+/// Bit position for a context in the propagation bitmask used by
+/// `RenderGraph::propagate_contexts` -- one bit per `Context` variant.
+fn context_bit(context: Context) -> u64 {
+    1 << context.priority()
+}
+
+/// Inverse of `context_bit`: resolves a single-bit mask back to its
+/// `Context`, or `None` if the mask doesn't correspond to exactly one.
+fn context_from_bit(mask: u64) -> Option<Context> {
+    [Context::Visual, Context::Audio, Context::Compute]
+        .into_iter()
+        .find(|&context| context_bit(context) == mask)
+}
+
+/// Collects the instance names an expression depends on into `deps`, and
+/// separately records in `delayed` which of those are read through a
+/// one-frame-delay marker (`StrandAccessExpr::delayed`) -- a declared
+/// feedback read rather than an ordinary same-frame dependency. `delayed`
+/// is always a subset of `deps`: the edge still gets built (so typing and
+/// cycle detection see it), just classified as `EdgeType::Feedback`
+/// instead of `Normal`.
+fn find_deps_in_expr(expr: &ASTNode, deps: &mut HashSet<String>, delayed: &mut HashSet<String>) {
     match expr {
         ASTNode::StrandAccess(access) => {
             if let ASTNode::Var(var) = &*access.base {
-                deps.insert(var.name.clone());
+                deps.insert(var.name.resolve().to_string());
+                if access.delayed {
+                    delayed.insert(var.name.resolve().to_string());
+                }
             }
         }
         ASTNode::StrandRemap(remap) => {
             if let ASTNode::Var(var) = &*remap.base {
-                deps.insert(var.name.clone());
+                deps.insert(var.name.resolve().to_string());
             }
             for mapping in &remap.mappings {
-                find_deps_in_expr(&mapping.expr, deps);
+                find_deps_in_expr(&mapping.expr, deps, delayed);
             }
         }
         ASTNode::Binary(bin) => {
-            find_deps_in_expr(&bin.left, deps);
-            find_deps_in_expr(&bin.right, deps);
+            find_deps_in_expr(&bin.left, deps, delayed);
+            find_deps_in_expr(&bin.right, deps, delayed);
         }
         ASTNode::Unary(un) => {
-            find_deps_in_expr(&un.expr, deps);
+            find_deps_in_expr(&un.expr, deps, delayed);
         }
         ASTNode::Call(call) => {
-            for arg in &call.args {
-                find_deps_in_expr(arg, deps);
+            if let Some(access) = prev_call_access(call) {
+                if let ASTNode::Var(var) = &*access.base {
+                    deps.insert(var.name.resolve().to_string());
+                    delayed.insert(var.name.resolve().to_string());
+                }
+            } else {
+                for arg in &call.args {
+                    find_deps_in_expr(arg, deps, delayed);
+                }
             }
         }
         ASTNode::If(if_expr) => {
-            find_deps_in_expr(&if_expr.condition, deps);
-            find_deps_in_expr(&if_expr.then_expr, deps);
-            find_deps_in_expr(&if_expr.else_expr, deps);
+            find_deps_in_expr(&if_expr.condition, deps, delayed);
+            find_deps_in_expr(&if_expr.then_expr, deps, delayed);
+            find_deps_in_expr(&if_expr.else_expr, deps, delayed);
+        }
+        ASTNode::Match(match_expr) => {
+            find_deps_in_expr(&match_expr.scrutinee, deps, delayed);
+            for arm in &match_expr.arms {
+                find_deps_in_expr(&arm.body, deps, delayed);
+            }
         }
         ASTNode::Tuple(tuple) => {
             for item in &tuple.items {
-                find_deps_in_expr(item, deps);
+                find_deps_in_expr(item, deps, delayed);
             }
         }
         ASTNode::Index(index) => {
-            find_deps_in_expr(&index.base, deps);
-            find_deps_in_expr(&index.index, deps);
+            find_deps_in_expr(&index.base, deps, delayed);
+            find_deps_in_expr(&index.index, deps, delayed);
         }
         ASTNode::Num(_) | ASTNode::Str(_) | ASTNode::Var(_) | ASTNode::Me(_) => {}
         _ => {}
     }
 }
 
+/// Recognizes `prev(a.x)` as a call-syntax alternative to
+/// `StrandAccessExpr::delayed` for marking a one-frame-delay read --
+/// useful since `weft.pest` has no delay-marker token of its own yet, so a
+/// parsed `StrandAccess` can never carry `delayed: true` (see the
+/// construction sites in `parser.rs`), while a plain `prev(...)` call
+/// already parses today.
+fn prev_call_access(call: &CallExpr) -> Option<&StrandAccessExpr> {
+    let ASTNode::Var(name) = call.name.as_ref() else {
+        return None;
+    };
+    if name.name != "prev" {
+        return None;
+    }
+    match call.args.as_slice() {
+        [ASTNode::StrandAccess(access)] => Some(access),
+        _ => None,
+    }
+}
+
 fn find_output_deps_in_expr(expr: &ASTNode, deps: &mut Vec<(String, String)>) {
     match expr {
         ASTNode::StrandAccess(access) => {
             if let ASTNode::Var(base_var) = &*access.base {
                 if let ASTNode::Var(out_var) = &*access.out {
-                    deps.push((base_var.name.clone(), out_var.name.clone()));
+                    deps.push((base_var.name.resolve().to_string(), out_var.name.resolve().to_string()));
                 }
             }
         }
         ASTNode::StrandRemap(remap) => {
             if let ASTNode::Var(base_var) = &*remap.base {
-                deps.push((base_var.name.clone(), remap.strand.clone()));
+                deps.push((base_var.name.resolve().to_string(), remap.strand.resolve().to_string()));
             }
             for mapping in &remap.mappings {
                 find_output_deps_in_expr(&mapping.expr, deps);
@@ -818,6 +1608,12 @@ fn find_output_deps_in_expr(expr: &ASTNode, deps: &mut Vec<(String, String)>) {
             find_output_deps_in_expr(&if_expr.then_expr, deps);
             find_output_deps_in_expr(&if_expr.else_expr, deps);
         }
+        ASTNode::Match(match_expr) => {
+            find_output_deps_in_expr(&match_expr.scrutinee, deps);
+            for arm in &match_expr.arms {
+                find_output_deps_in_expr(&arm.body, deps);
+            }
+        }
         ASTNode::Tuple(tuple) => {
             for item in &tuple.items {
                 find_output_deps_in_expr(item, deps);
@@ -834,7 +1630,7 @@ fn check_node_type(expr: &ASTNode, env: &Env) -> NodeType {
     match expr {
         ASTNode::Call(call_expr) => {
             if let ASTNode::Var(var) = &*call_expr.name {
-                if env.spindles.contains_key(&var.name) {
+                if env.spindles.contains_key(var.name.resolve()) {
                     NodeType::Spindle
                 } else {
                     NodeType::Builtin
@@ -854,12 +1650,22 @@ mod tests {
 
     fn var(name: &str) -> ASTNode {
         ASTNode::Var(VarExpr {
-            name: name.to_string(),
+            name: crate::symbol::intern(name),
+            span: Span::synthetic(),
         })
     }
 
     fn num(value: f64) -> ASTNode {
-        ASTNode::Num(NumExpr { v: value })
+        let kind = if value.fract() == 0.0 {
+            NumKind::Int(value as i64)
+        } else {
+            NumKind::Float(value)
+        };
+        ASTNode::Num(NumExpr {
+            v: value,
+            kind,
+            span: Span::synthetic(),
+        })
     }
 
     fn instance_binding(name: &str, outputs: Vec<&str>, expr: ASTNode) -> ASTNode {
@@ -867,6 +1673,7 @@ mod tests {
             name: name.to_string(),
             outputs: outputs.iter().map(|s| s.to_string()).collect(),
             expr: Box::new(expr),
+            span: Span::synthetic(),
         })
     }
 
@@ -874,6 +1681,25 @@ mod tests {
         ASTNode::StrandAccess(StrandAccessExpr {
             base: Box::new(var(base)),
             out: Box::new(var(out)),
+            delayed: false,
+            span: Span::synthetic(),
+        })
+    }
+
+    fn delayed_strand_access(base: &str, out: &str) -> ASTNode {
+        ASTNode::StrandAccess(StrandAccessExpr {
+            base: Box::new(var(base)),
+            out: Box::new(var(out)),
+            delayed: true,
+            span: Span::synthetic(),
+        })
+    }
+
+    fn prev_call(base: &str, out: &str) -> ASTNode {
+        ASTNode::Call(CallExpr {
+            name: Box::new(var("prev")),
+            args: vec![strand_access(base, out)],
+            span: Span::synthetic(),
         })
     }
 
@@ -883,6 +1709,7 @@ mod tests {
             args: vec![],
             named_args: HashMap::new(),
             positional_args,
+            span: Span::synthetic(),
         })
     }
 
@@ -990,6 +1817,70 @@ mod tests {
         assert!(meta.references.is_empty());
     }
 
+    #[test]
+    fn test_ambiguous_node_duplicates_regardless_of_backend_declaration_order() {
+        // Same shape as `test_shared_computation_gets_duplicated`, but with
+        // the two backend statements swapped, to pin down that
+        // `propagate_contexts` resolves "shared" by merging both of its
+        // already-typed dependents' bits in the same wave rather than
+        // committing to whichever one it happens to visit first. A
+        // FIFO-queue worklist that types a node as soon as a single bit
+        // reaches it would make "shared" concretely Visual or Audio here
+        // depending on traversal order, instead of leaving it ambiguous
+        // for phase 2 to duplicate.
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("shared", vec!["val"], num(42.0)),
+            instance_binding("visual_out", vec!["color"], strand_access("shared", "val")),
+            instance_binding("audio_out", vec!["amp"], strand_access("shared", "val")),
+            backend("play", vec![strand_access("audio_out", "amp")]),
+            backend("display", vec![strand_access("visual_out", "color")]),
+        ]);
+        let env = test_env();
+        let result = graph.build(&prog, &env);
+        assert!(result.is_ok());
+        let meta = result.unwrap();
+        assert_eq!(meta.subgraphs.len(), 2);
+        let visual = &meta.subgraphs[&Context::Visual];
+        assert!(visual
+            .node_names
+            .iter()
+            .any(|n| n.contains("shared") && n.contains("visual")));
+        let audio = &meta.subgraphs[&Context::Audio];
+        assert!(audio
+            .node_names
+            .iter()
+            .any(|n| n.contains("shared") && n.contains("audio")));
+        assert!(meta.references.is_empty());
+    }
+
+    #[test]
+    fn test_three_way_ambiguous_node_duplicates_into_all_contexts() {
+        // A node read directly by all three backend contexts should end up
+        // with a three-bit mask after propagation, not get concretely
+        // typed to whichever context's bit reaches it first.
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("shared", vec!["val"], num(1.0)),
+            backend("display", vec![strand_access("shared", "val")]),
+            backend("play", vec![strand_access("shared", "val")]),
+            backend("compute", vec![strand_access("shared", "val")]),
+        ]);
+        let env = test_env();
+        let result = graph.build(&prog, &env);
+        assert!(result.is_ok());
+        let meta = result.unwrap();
+        assert_eq!(meta.subgraphs.len(), 3);
+        for context in [Context::Visual, Context::Audio, Context::Compute] {
+            let subgraph = &meta.subgraphs[&context];
+            assert!(
+                subgraph.node_names.iter().any(|n| n.contains("shared")),
+                "expected a duplicate of 'shared' in {:?}",
+                context
+            );
+        }
+    }
+
     #[test]
     fn test_cross_context_reference() {
         let mut graph = RenderGraph::new();
@@ -1049,7 +1940,16 @@ mod tests {
             instance_binding("root", vec!["val"], num(10.0)),
             instance_binding("left", vec!["a"], strand_access("root", "val")),
             instance_binding("right", vec!["b"], strand_access("root", "val")),
-            instance_binding("merge", vec!["c"], strand_access("left", "a")),
+            instance_binding(
+                "merge",
+                vec!["c"],
+                ASTNode::Binary(BinaryExpr {
+                    op: "+".to_string(),
+                    left: Box::new(strand_access("left", "a")),
+                    right: Box::new(strand_access("right", "b")),
+                    span: Span::synthetic(),
+                }),
+            ),
             backend("display", vec![strand_access("merge", "c")]),
         ]);
         let env = test_env();
@@ -1069,6 +1969,81 @@ mod tests {
         assert!(pos("left") < pos("merge"));
     }
 
+    #[test]
+    fn test_diamond_dependency_groups_independent_nodes_into_one_stage() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("root", vec!["val"], num(10.0)),
+            instance_binding("left", vec!["a"], strand_access("root", "val")),
+            instance_binding("right", vec!["b"], strand_access("root", "val")),
+            instance_binding(
+                "merge",
+                vec!["c"],
+                ASTNode::Binary(BinaryExpr {
+                    op: "+".to_string(),
+                    left: Box::new(strand_access("left", "a")),
+                    right: Box::new(strand_access("right", "b")),
+                    span: Span::synthetic(),
+                }),
+            ),
+            backend("display", vec![strand_access("merge", "c")]),
+        ]);
+        let env = test_env();
+        let meta = graph.build(&prog, &env).unwrap();
+        let visual = &meta.subgraphs[&Context::Visual];
+
+        assert_eq!(visual.stages.len(), 3);
+        assert_eq!(visual.stages[0], vec!["root".to_string()]);
+        let mut stage1 = visual.stages[1].clone();
+        stage1.sort();
+        assert_eq!(stage1, vec!["left".to_string(), "right".to_string()]);
+        assert_eq!(visual.stages[2], vec!["merge".to_string()]);
+    }
+
+    #[test]
+    fn test_execution_order_breaks_ties_by_declaration_order() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("z", vec!["a"], num(1.0)),
+            instance_binding("y", vec!["b"], num(2.0)),
+            instance_binding("x", vec!["c"], num(3.0)),
+            backend(
+                "display",
+                vec![
+                    strand_access("z", "a"),
+                    strand_access("y", "b"),
+                    strand_access("x", "c"),
+                ],
+            ),
+        ]);
+        let env = test_env();
+        let meta = graph.build(&prog, &env).unwrap();
+        let visual = &meta.subgraphs[&Context::Visual];
+
+        assert_eq!(visual.execution_order, vec!["z", "y", "x"]);
+    }
+
+    #[test]
+    fn test_dead_strand_is_pruned_from_execution_order() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("root", vec!["val"], num(10.0)),
+            instance_binding("used", vec!["a"], strand_access("root", "val")),
+            instance_binding("scratch", vec!["b"], strand_access("root", "val")),
+            backend("display", vec![strand_access("used", "a")]),
+        ]);
+        let env = test_env();
+        let meta = graph.build(&prog, &env).unwrap();
+        let visual = &meta.subgraphs[&Context::Visual];
+
+        assert_eq!(visual.execution_order, vec!["root", "used"]);
+        assert!(!visual.node_names.contains(&"scratch".to_string()));
+        assert_eq!(
+            visual.stages,
+            vec![vec!["root".to_string()], vec!["used".to_string()]]
+        );
+    }
+
     #[test]
     fn test_deep_dependency_chain() {
         let mut graph = RenderGraph::new();
@@ -1162,4 +2137,227 @@ mod tests {
             .unwrap();
         assert!(visual_pos < audio_pos);
     }
+
+    #[test]
+    fn test_describe_context_cycle_names_contexts_and_references() {
+        let mut context_dag = DiGraph::new();
+        let visual = context_dag.add_node(Context::Visual);
+        let audio = context_dag.add_node(Context::Audio);
+        let compute = context_dag.add_node(Context::Compute);
+        context_dag.add_edge(compute, visual, ());
+        context_dag.add_edge(visual, audio, ());
+        context_dag.add_edge(audio, compute, ());
+
+        let references = vec![
+            Reference {
+                from_context: Context::Visual,
+                from_node: "a".to_string(),
+                to_context: Context::Compute,
+                to_node: "c".to_string(),
+            },
+            Reference {
+                from_context: Context::Audio,
+                from_node: "b".to_string(),
+                to_context: Context::Visual,
+                to_node: "a".to_string(),
+            },
+            Reference {
+                from_context: Context::Compute,
+                from_node: "c".to_string(),
+                to_context: Context::Audio,
+                to_node: "b".to_string(),
+            },
+        ];
+
+        let err = describe_context_cycle(&context_dag, &references);
+        let message = err.to_string();
+        assert!(message.contains("circular dependency between contexts"));
+        assert!(message.contains("Visual"));
+        assert!(message.contains("Audio"));
+        assert!(message.contains("Compute"));
+        assert!(message.contains("a(Visual) depends on c(Compute)"));
+    }
+
+    #[test]
+    fn test_describe_subgraph_cycle_names_member_instances() {
+        let mut subgraph: DiGraph<GraphNode, ()> = DiGraph::new();
+        let a = subgraph.add_node(GraphNode {
+            instance_name: "a".to_string(),
+            original_name: None,
+            node_type: NodeType::Expression,
+            context: Some(Context::Visual),
+            outputs: HashMap::new(),
+            deps: HashSet::new(),
+            delayed_deps: HashSet::new(),
+            output_deps: HashMap::new(),
+            required_outputs: HashSet::new(),
+            is_duplicate: false,
+            typed_by_child: None,
+            declaration_index: 0,
+        });
+        let mut node_b = subgraph[a].clone();
+        node_b.instance_name = "b".to_string();
+        let b = subgraph.add_node(node_b);
+        subgraph.add_edge(a, b, ());
+        subgraph.add_edge(b, a, ());
+
+        let err = describe_subgraph_cycle(&subgraph, Context::Visual);
+        let message = err.to_string();
+        assert!(message.contains("Visual"));
+        assert!(message.contains("a"));
+        assert!(message.contains("b"));
+    }
+
+    #[test]
+    fn test_rebuild_marks_unchanged_nodes_clean() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], num(1.0)),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("b", "y")]),
+        ]);
+        let env = test_env();
+        graph.build(&prog, &env).unwrap();
+
+        let (meta, diff) = graph.rebuild(&prog, &env).unwrap();
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(diff.dirty.is_empty());
+        assert_eq!(meta.subgraphs[&Context::Visual].execution_order.len(), 2);
+    }
+
+    #[test]
+    fn test_rebuild_propagates_dirty_to_dependents() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], num(1.0)),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("b", "y")]),
+        ]);
+        let env = test_env();
+        graph.build(&prog, &env).unwrap();
+
+        let changed_prog = program(vec![
+            instance_binding("a", vec!["x"], num(2.0)),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("b", "y")]),
+        ]);
+        let (_, diff) = graph.rebuild(&changed_prog, &env).unwrap();
+        assert!(diff.dirty.contains("a"));
+        assert!(diff.dirty.contains("b"));
+    }
+
+    #[test]
+    fn test_rebuild_tracks_added_and_removed_nodes() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], num(1.0)),
+            backend("display", vec![strand_access("a", "x")]),
+        ]);
+        let env = test_env();
+        graph.build(&prog, &env).unwrap();
+
+        let changed_prog = program(vec![
+            instance_binding("b", vec!["y"], num(2.0)),
+            backend("display", vec![strand_access("b", "y")]),
+        ]);
+        let (_, diff) = graph.rebuild(&changed_prog, &env).unwrap();
+        assert!(diff.added.contains("b"));
+        assert!(diff.removed.contains("a"));
+    }
+
+    #[test]
+    fn test_build_with_cache_reuses_unchanged_context_across_processes() {
+        let cache_path = std::env::temp_dir().join("weft_render_graph_test_build_with_cache.cache");
+        let _ = std::fs::remove_file(&cache_path);
+
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], num(1.0)),
+            backend("display", vec![strand_access("a", "x")]),
+            instance_binding("b", vec!["y"], num(2.0)),
+            backend("play", vec![strand_access("b", "y")]),
+        ]);
+        let env = test_env();
+
+        // First "process": builds from scratch and writes the cache.
+        let mut first = RenderGraph::new();
+        first.build_with_cache(&prog, &env, &cache_path).unwrap();
+
+        // Second "process": a fresh RenderGraph with nothing in memory,
+        // loading the same cache from disk, with "b" changed.
+        let changed_prog = program(vec![
+            instance_binding("a", vec!["x"], num(1.0)),
+            backend("display", vec![strand_access("a", "x")]),
+            instance_binding("b", vec!["y"], num(99.0)),
+            backend("play", vec![strand_access("b", "y")]),
+        ]);
+        let mut second = RenderGraph::new();
+        let (_, report) = second
+            .build_with_cache(&changed_prog, &env, &cache_path)
+            .unwrap();
+
+        assert!(report.reused_contexts.contains(&Context::Visual));
+        assert!(!report.reused_contexts.contains(&Context::Audio));
+
+        let _ = std::fs::remove_file(&cache_path);
+    }
+
+    #[test]
+    fn test_build_fails_on_undefined_instance_reference() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], strand_access("typo", "x")),
+            backend("display", vec![strand_access("a", "x")]),
+        ]);
+        let env = test_env();
+        let err = graph.build(&prog, &env).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("typo"));
+        assert!(message.contains("a"));
+    }
+
+    #[test]
+    fn test_build_fails_on_cycle_without_feedback_marker() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], strand_access("b", "y")),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("a", "x")]),
+        ]);
+        let env = test_env();
+        let err = graph.build(&prog, &env).unwrap_err();
+        assert!(err.to_string().contains("feedback"));
+    }
+
+    #[test]
+    fn test_build_succeeds_on_cycle_with_feedback_marker() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], delayed_strand_access("b", "y")),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("a", "x")]),
+        ]);
+        let env = test_env();
+        let meta = graph.build(&prog, &env).unwrap();
+        assert!(meta
+            .feedback
+            .iter()
+            .any(|r| r.from_node == "a" && r.to_node == "b"));
+    }
+
+    #[test]
+    fn test_build_succeeds_on_cycle_broken_by_prev_call() {
+        let mut graph = RenderGraph::new();
+        let prog = program(vec![
+            instance_binding("a", vec!["x"], prev_call("b", "y")),
+            instance_binding("b", vec!["y"], strand_access("a", "x")),
+            backend("display", vec![strand_access("a", "x")]),
+        ]);
+        let env = test_env();
+        let meta = graph.build(&prog, &env).unwrap();
+        assert!(meta
+            .feedback
+            .iter()
+            .any(|r| r.from_node == "a" && r.to_node == "b"));
+    }
 }
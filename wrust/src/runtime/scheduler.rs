@@ -0,0 +1,154 @@
+//! Beat-synchronized event scheduling for `Coordinator`.
+//!
+//! `Env` only ever reports the *current* beat -- there was no way for a
+//! spindle to say "change this output on the next downbeat", or for the
+//! audio backend to get a parameter change far enough ahead of the
+//! realtime callback to apply it sample-accurately. `Scheduler` holds
+//! events keyed by an absolute [`Superbeats`] position; on every tick
+//! `Coordinator::dispatch_scheduled_events` computes a run-ahead window
+//! `[now, now + lookahead]` -- like a DAW scheduling a block of samples
+//! before its deadline -- pops everything due inside it, and hands each
+//! event to the backend that owns its `(instance, output)` target via
+//! `Backend::schedule_value_change`.
+
+use super::env::Superbeats;
+
+/// The new value to apply once playback reaches a `ScheduledEvent`'s
+/// `at_beat`: either a literal value, or a callback computed at dispatch
+/// time (for e.g. a ramp endpoint that depends on state read late).
+///
+/// The callback is `Send` so `Scheduler` (and therefore `Coordinator`,
+/// which guards one behind a `Mutex`) stays safe to share across the
+/// worker threads `Coordinator::execute` spawns for a parallel dependency
+/// level.
+pub enum ScheduledValue {
+    Value(f64),
+    Callback(Box<dyn FnOnce() -> f64 + Send>),
+}
+
+impl ScheduledValue {
+    pub(crate) fn resolve(self) -> f64 {
+        match self {
+            ScheduledValue::Value(v) => v,
+            ScheduledValue::Callback(f) => f(),
+        }
+    }
+}
+
+/// A single `(instance, output)` change due at `at_beat`.
+pub struct ScheduledEvent {
+    pub at_beat: Superbeats,
+    pub instance: String,
+    pub output: String,
+    pub value: ScheduledValue,
+}
+
+impl ScheduledEvent {
+    pub fn new(
+        at_beat: Superbeats,
+        instance: impl Into<String>,
+        output: impl Into<String>,
+        value: ScheduledValue,
+    ) -> Self {
+        Self {
+            at_beat,
+            instance: instance.into(),
+            output: output.into(),
+            value,
+        }
+    }
+}
+
+/// Holds not-yet-due events sorted by `at_beat`, cheapest to pop a
+/// run-ahead window from the front of.
+#[derive(Default)]
+pub struct Scheduler {
+    events: Vec<ScheduledEvent>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `event`, keeping `events` sorted by `at_beat` (mirrors
+    /// `TempoMap::insert_point`'s sorted-insert approach).
+    pub fn schedule(&mut self, event: ScheduledEvent) {
+        let idx = self.events.partition_point(|e| e.at_beat <= event.at_beat);
+        self.events.insert(idx, event);
+    }
+
+    /// Removes and returns every event whose `at_beat` is at or before
+    /// `now + lookahead`, in ascending `at_beat` order. Events whose
+    /// `at_beat` is already behind `now` (the scheduler wasn't ticked in
+    /// time) are included too rather than dropped silently -- the same
+    /// way a DAW flushes a late block instead of losing it.
+    pub fn drain_due(&mut self, now: Superbeats, lookahead: Superbeats) -> Vec<ScheduledEvent> {
+        let horizon = now + lookahead;
+        let split = self.events.partition_point(|e| e.at_beat <= horizon);
+        self.events.drain(..split).collect()
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beats(n: f64) -> Superbeats {
+        Superbeats::from_beats(n)
+    }
+
+    #[test]
+    fn drain_due_pops_events_in_the_lookahead_window_in_order() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(ScheduledEvent::new(
+            beats(4.0),
+            "osc1",
+            "freq",
+            ScheduledValue::Value(440.0),
+        ));
+        scheduler.schedule(ScheduledEvent::new(
+            beats(1.0),
+            "osc1",
+            "freq",
+            ScheduledValue::Value(220.0),
+        ));
+        scheduler.schedule(ScheduledEvent::new(
+            beats(2.0),
+            "osc1",
+            "freq",
+            ScheduledValue::Value(330.0),
+        ));
+
+        let due = scheduler.drain_due(beats(0.0), beats(2.5));
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].at_beat, beats(1.0));
+        assert_eq!(due[1].at_beat, beats(2.0));
+        assert_eq!(scheduler.len(), 1);
+    }
+
+    #[test]
+    fn drain_due_still_fires_events_that_are_already_late() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(ScheduledEvent::new(
+            beats(1.0),
+            "osc1",
+            "freq",
+            ScheduledValue::Value(220.0),
+        ));
+
+        let due = scheduler.drain_due(beats(5.0), beats(0.1));
+        assert_eq!(due.len(), 1);
+    }
+
+    #[test]
+    fn scheduled_value_callback_is_resolved_lazily() {
+        let value = ScheduledValue::Callback(Box::new(|| 42.0));
+        assert_eq!(value.resolve(), 42.0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Global identifier interner, mirroring the `arc-interner`/`dashmap`
+//! approach the outrun-parser crate uses for the same problem: every
+//! identifier the parser produces for a name field (`Var.name`,
+//! `Assignment.name`, `SpindleDef.name`/`inputs`/`outputs`,
+//! `StrandRemap.strand`, `Me.field`) is interned once into a process-wide
+//! table and referenced afterward by a `Copy` integer handle instead of a
+//! freshly allocated, re-hashed `String`. Comparing two `Symbol`s is an
+//! integer comparison instead of a byte compare; lookups keyed by
+//! `String` (such as `env.spindles`) still go through `resolve()`.
+//!
+//! Interned strings are never freed -- identifier counts in a weft
+//! program are small and bounded by source size, so leaking the backing
+//! allocations for the process lifetime is the same tradeoff `lasso` and
+//! `string-cache` make, and it lets [`Symbol::resolve`] hand back a
+//! `&'static str` with no further locking or cloning.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// An interned identifier. Two `Symbol`s are equal iff the strings they
+/// were interned from are equal; use [`Symbol::resolve`] to get the
+/// string back for display or diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Looks up the string this symbol was interned from. Never panics:
+    /// every live `Symbol` was handed out by [`intern`], which always
+    /// records a backing entry first.
+    pub fn resolve(self) -> &'static str {
+        table().lock().unwrap().resolve(self)
+    }
+}
+
+/// Lets call sites and test assertions compare a `Symbol` against a
+/// string literal (`v.name == "foo"`) without an explicit `resolve()`.
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.resolve() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.resolve() == *other
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(&self, other: &Symbol) -> bool {
+        other.resolve() == self
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.resolve())
+    }
+}
+
+/// Serializes as the resolved string rather than the raw id, so
+/// `--format json` output stays human-readable.
+impl Serialize for Symbol {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.resolve())
+    }
+}
+
+struct Interner {
+    strings: Vec<&'static str>,
+    lookup: HashMap<&'static str, u32>,
+}
+
+impl Interner {
+    fn new() -> Self {
+        Self { strings: Vec::new(), lookup: HashMap::new() }
+    }
+
+    fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&id) = self.lookup.get(s) {
+            return Symbol(id);
+        }
+
+        let leaked: &'static str = Box::leak(s.to_string().into_boxed_str());
+        let id = self.strings.len() as u32;
+        self.lookup.insert(leaked, id);
+        self.strings.push(leaked);
+        Symbol(id)
+    }
+
+    fn resolve(&self, sym: Symbol) -> &'static str {
+        self.strings[sym.0 as usize]
+    }
+}
+
+fn table() -> &'static Mutex<Interner> {
+    static TABLE: OnceLock<Mutex<Interner>> = OnceLock::new();
+    TABLE.get_or_init(|| Mutex::new(Interner::new()))
+}
+
+/// Interns `s`, returning the same [`Symbol`] for every prior or
+/// subsequent call with an equal string.
+pub fn intern(s: &str) -> Symbol {
+    table().lock().unwrap().intern(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_string_interns_to_the_same_symbol() {
+        assert_eq!(intern("blur"), intern("blur"));
+    }
+
+    #[test]
+    fn different_strings_intern_to_different_symbols() {
+        assert_ne!(intern("blur"), intern("sharpen"));
+    }
+
+    #[test]
+    fn resolve_round_trips_the_original_string() {
+        assert_eq!(intern("feedback_loop").resolve(), "feedback_loop");
+    }
+
+    #[test]
+    fn symbol_compares_equal_to_its_source_str() {
+        assert_eq!(intern("blur"), "blur");
+        assert_eq!(vec![intern("a"), intern("b")], vec!["a", "b"]);
+    }
+}
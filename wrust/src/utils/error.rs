@@ -1,15 +1,304 @@
+use crate::ast::Span;
+use crate::parser::Rule;
+use std::path::PathBuf;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, WeftError>;
 
 #[derive(Debug, Error)]
 pub enum WeftError {
-    #[error("Parse error: {0}")]
-    Parse(String),
-
     #[error("{0}")]
     Runtime(String),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// A file failed to parse. Keeps the path alongside the underlying
+    /// `ParseError` so the message can name the file the grammar choked
+    /// on instead of just the bare line number.
+    #[error("failed to load {path:?}: {source}")]
+    Load {
+        path: PathBuf,
+        #[source]
+        source: ParseError,
+    },
+
+    /// An error from the render-graph resolve stage (instance references,
+    /// backend lookups), kept as a distinct variant so `.source()` can
+    /// walk down into the specific `ResolveError` that caused it.
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    /// A runtime or parse error with a known source location. Produced by
+    /// `.at(span)` below; the CLI renders it with a caret-underlined
+    /// snippet of the offending source line.
+    #[error("{message}")]
+    Located { span: Span, message: String },
+
+    /// A breadcrumb layer added by `.context()`/`.with_context()` (see
+    /// `ResultExt` below): wraps an inner failure with a description of
+    /// what the caller was doing, so a chain of these reads back like a
+    /// stack trace to the top-level evaluation request.
+    #[error("{message}: {source}")]
+    Context {
+        message: String,
+        #[source]
+        source: Box<WeftError>,
+    },
+}
+
+/// Can't `#[derive(PartialEq)]` since `std::io::Error` isn't `PartialEq`;
+/// compare it on `ErrorKind` only, as is commonly done, so table-driven
+/// tests can assert `compile(src).unwrap_err() == expected` without
+/// matching on rendered strings.
+impl PartialEq for WeftError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WeftError::Runtime(a), WeftError::Runtime(b)) => a == b,
+            (WeftError::Io(a), WeftError::Io(b)) => a.kind() == b.kind(),
+            (
+                WeftError::Load {
+                    path: p1,
+                    source: s1,
+                },
+                WeftError::Load {
+                    path: p2,
+                    source: s2,
+                },
+            ) => p1 == p2 && s1 == s2,
+            (WeftError::Resolve(a), WeftError::Resolve(b)) => a == b,
+            (
+                WeftError::Located {
+                    span: s1,
+                    message: m1,
+                },
+                WeftError::Located {
+                    span: s2,
+                    message: m2,
+                },
+            ) => s1 == s2 && m1 == m2,
+            (
+                WeftError::Context {
+                    message: m1,
+                    source: s1,
+                },
+                WeftError::Context {
+                    message: m2,
+                    source: s2,
+                },
+            ) => m1 == m2 && s1 == s2,
+            _ => false,
+        }
+    }
+}
+
+/// Adds snafu-style context annotation to any `Result` whose error
+/// converts into `WeftError`, so evaluation code can cheaply note "what
+/// was I doing" at each call boundary without losing the original error
+/// as its `#[source]`.
+pub trait ResultExt<T> {
+    /// Wraps the error (if any) with a breadcrumb `message`, eagerly
+    /// built. Prefer `with_context` if building `message` isn't free.
+    fn context(self, message: impl Into<String>) -> Result<T>;
+
+    /// Like `context`, but only calls `f` to build the message on the
+    /// error path, so the success path pays nothing for it.
+    fn with_context<F, S>(self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>;
+}
+
+impl<T, E> ResultExt<T> for std::result::Result<T, E>
+where
+    E: Into<WeftError>,
+{
+    fn context(self, message: impl Into<String>) -> Result<T> {
+        self.map_err(|e| WeftError::Context {
+            message: message.into(),
+            source: Box::new(e.into()),
+        })
+    }
+
+    fn with_context<F, S>(self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>,
+    {
+        self.map_err(|e| WeftError::Context {
+            message: f().into(),
+            source: Box::new(e.into()),
+        })
+    }
+}
+
+/// A syntax error surfaced by the grammar. Wraps pest's own error so the
+/// causal chain is preserved, but exposes the 1-based source line and a
+/// byte-offset `span` directly so callers don't have to re-parse pest's
+/// message to find them. `labels` holds secondary spans worth pointing at
+/// alongside the primary one (e.g. "matching delimiter opened here").
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub span: Span,
+    pub labels: Vec<(Span, String)>,
+    message: String,
+    #[source]
+    source: Box<pest::error::Error<Rule>>,
+}
+
+impl ParseError {
+    pub fn from_pest(err: pest::error::Error<Rule>) -> Self {
+        let line = match err.line_col() {
+            pest::error::LineColLocation::Pos((line, _)) => line,
+            pest::error::LineColLocation::Span((line, _), _) => line,
+        };
+        let span = match &err.location {
+            pest::error::InputLocation::Pos(pos) => Span::new(*pos, *pos, None),
+            pest::error::InputLocation::Span((start, end)) => Span::new(*start, *end, None),
+        };
+        let message = err.to_string();
+        ParseError {
+            line,
+            span,
+            labels: Vec::new(),
+            message,
+            source: Box::new(err),
+        }
+    }
+
+    /// Attaches a secondary label pointing at another span related to this
+    /// error (e.g. "opening delimiter here"). Rendered as its own code
+    /// frame underneath the primary one.
+    pub fn with_label(mut self, span: Span, label: impl Into<String>) -> Self {
+        self.labels.push((span, label.into()));
+        self
+    }
+
+    /// A code-frame rendering of this error against `source`: the primary
+    /// span's line with a caret underline, followed by one frame per
+    /// secondary label.
+    pub fn render(&self, source: &str) -> String {
+        render_span_with_labels(source, &self.span, &self.message, &self.labels)
+    }
+}
+
+/// Lets a raw grammar failure flow straight into `WeftError` via `?`
+/// (see `parser::parse`), landing as `Located` with the same span the
+/// grammar pointed at.
+impl From<ParseError> for WeftError {
+    fn from(err: ParseError) -> Self {
+        let message = err.to_string();
+        WeftError::Located {
+            span: err.span,
+            message,
+        }
+    }
+}
+
+/// Can't `#[derive(PartialEq)]` since `pest::error::Error` isn't
+/// `PartialEq`; compare on the fields that matter for a test asserting
+/// "this source produced this parse error" instead.
+impl PartialEq for ParseError {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line && self.span == other.span && self.labels == other.labels
+    }
+}
+
+/// Failures from resolving a parsed `Program` into a compiled render
+/// graph: references to instances or backends that don't exist.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ResolveError {
+    #[error("undefined instance reference(s):\n{0}")]
+    UndefinedInstances(String),
+
+    #[error("Unknown backend: {0}")]
+    UnknownBackend(String),
+}
+
+impl WeftError {
+    /// Attaches a source span to this error, wrapping it in `Located` so
+    /// the original message is preserved.
+    pub fn at(self, span: Span) -> Self {
+        let message = self.to_string();
+        WeftError::Located { span, message }
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            WeftError::Located { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+
+    /// Renders a caret-underlined snippet of `source` pointing at this
+    /// error's span, if it has one, falling back to just the message.
+    pub fn render(&self, source: &str) -> String {
+        let Some(span) = self.span() else {
+            return self.to_string();
+        };
+        render_span(source, span, &self.to_string())
+    }
+}
+
+/// Renders a caret-underlined snippet of `source` pointing at `span`,
+/// prefixed by `header`. Shared by `WeftError::render` and
+/// `crate::diagnostics::Diagnostic::render`, which both point at a
+/// source location the same way.
+pub(crate) fn render_span(source: &str, span: &Span, header: &str) -> String {
+    render_span_with_labels(source, span, header, &[])
+}
+
+/// Like `render_span`, but follows the primary frame with one additional
+/// code frame per entry in `labels` — each prefixed by its own text
+/// instead of `header`. Used by `ParseError::render` for diagnostics that
+/// want to point at more than one place at once (e.g. the error site and
+/// an earlier, related declaration).
+pub(crate) fn render_span_with_labels(
+    source: &str,
+    span: &Span,
+    header: &str,
+    labels: &[(Span, String)],
+) -> String {
+    let mut out = match code_frame(source, span) {
+        Some(frame) => format!("{}\n{}", header, frame),
+        None => header.to_string(),
+    };
+    for (label_span, label) in labels {
+        if let Some(frame) = code_frame(source, label_span) {
+            out.push_str(&format!("\n{}\n{}", label, frame));
+        }
+    }
+    out
+}
+
+/// Builds the gutter + source line + caret row for `span`: `line:column`
+/// in the gutter, the containing line, and a caret row underlining the
+/// span's width (clamped to the line, since a span can run past EOL).
+/// Returns `None` if `span` falls outside `source`.
+fn code_frame(source: &str, span: &Span) -> Option<String> {
+    let mut offset = 0;
+    for (line_no, line) in source.lines().enumerate() {
+        let line_end = offset + line.len();
+        if span.start >= offset && span.start <= line_end {
+            let col = span.start - offset;
+            let caret_len = span
+                .end
+                .saturating_sub(span.start)
+                .max(1)
+                .min(line.len().saturating_sub(col).max(1));
+            let caret_line = format!("{}{}", " ".repeat(col), "^".repeat(caret_len));
+            return Some(format!(
+                "  --> line {}:{}\n  {}\n  {}",
+                line_no + 1,
+                col + 1,
+                line,
+                caret_line
+            ));
+        }
+        offset = line_end + 1;
+    }
+
+    None
 }